@@ -0,0 +1,90 @@
+//! Constant-product (fixed-product market maker) math for CPMM markets.
+//!
+//! The pool holds YES and NO reserves. Paying collateral into the pool mints
+//! complete sets (1 YES + 1 NO) into both reserves, and the trader takes out
+//! enough of their side to bring `yes_reserve * no_reserve` back to where it
+//! was. Selling runs the same trade in reverse. Every rounding step favours
+//! the pool, so the product never decreases.
+
+use crate::errors::Error;
+use crate::math;
+use crate::types::{CpmmPool, Side};
+
+/// Integer square root, rounded up.
+fn sqrt_ceil(n: i128) -> i128 {
+    if n <= 0 {
+        return 0;
+    }
+    let n = n as u128;
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    if x * x < n {
+        x += 1;
+    }
+    x as i128
+}
+
+/// Reserves as `(side, other side)`.
+fn reserves(pool: &CpmmPool, side: &Side) -> (i128, i128) {
+    if *side == Side::Yes {
+        (pool.yes_reserve, pool.no_reserve)
+    } else {
+        (pool.no_reserve, pool.yes_reserve)
+    }
+}
+
+fn set_reserves(pool: &mut CpmmPool, side: &Side, side_reserve: i128, other_reserve: i128) {
+    if *side == Side::Yes {
+        pool.yes_reserve = side_reserve;
+        pool.no_reserve = other_reserve;
+    } else {
+        pool.no_reserve = side_reserve;
+        pool.yes_reserve = other_reserve;
+    }
+}
+
+/// Buys shares of `side` with `amount` collateral, updating the reserves.
+/// Returns the shares received.
+pub fn buy(pool: &mut CpmmPool, side: &Side, amount: i128) -> Result<i128, Error> {
+    let (side_reserve, other_reserve) = reserves(pool, side);
+    let k = side_reserve
+        .checked_mul(other_reserve)
+        .ok_or(Error::Overflow)?;
+    let new_other = other_reserve
+        .checked_add(amount)
+        .ok_or(Error::Overflow)?;
+    // Round the remaining side reserve up so the product is preserved
+    let new_side = math::add(k, math::sub(new_other, 1)?)? / new_other;
+    set_reserves(pool, side, new_side, new_other);
+    math::sub(math::add(side_reserve, amount)?, new_side)
+}
+
+/// Sells `shares` of `side` back to the pool, updating the reserves.
+/// Returns the collateral paid out.
+pub fn sell(pool: &mut CpmmPool, side: &Side, shares: i128) -> Result<i128, Error> {
+    let (side_reserve, other_reserve) = reserves(pool, side);
+    let k = side_reserve
+        .checked_mul(other_reserve)
+        .ok_or(Error::Overflow)?;
+    let r_side = side_reserve
+        .checked_add(shares)
+        .ok_or(Error::Overflow)?;
+
+    // Burn x complete sets out of (r_side, other_reserve) such that
+    // (r_side - x) * (other_reserve - x) = k. The smaller root of the
+    // quadratic, rounded down.
+    let diff = math::sub(r_side, other_reserve)?;
+    let disc = diff
+        .checked_mul(diff)
+        .and_then(|d| d.checked_add(k.checked_mul(4)?))
+        .ok_or(Error::Overflow)?;
+    let amount_out = math::sub(math::add(r_side, other_reserve)?, sqrt_ceil(disc))? / 2;
+    let new_side = math::sub(r_side, amount_out)?;
+    let new_other = math::sub(other_reserve, amount_out)?;
+    set_reserves(pool, side, new_side, new_other);
+    Ok(amount_out)
+}
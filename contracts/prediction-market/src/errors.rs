@@ -16,4 +16,32 @@ pub enum Error {
     NothingToClaim = 10,
     AlreadyClaimed = 11,
     InvalidDeadline = 12,
+    InvalidLiquidity = 13,
+    InvalidMarketKind = 14,
+    NoLiquidity = 15,
+    InsufficientShares = 16,
+    SlippageExceeded = 17,
+    InvalidOutcome = 18,
+    InvalidOutcomeCount = 19,
+    InvalidRange = 20,
+    NoOracle = 21,
+    OraclePriceUnavailable = 22,
+    OraclePriceStale = 23,
+    NotProposed = 24,
+    ProposalPending = 25,
+    DisputeWindowClosed = 26,
+    DisputeWindowOpen = 27,
+    InvalidFee = 28,
+    TokenNotAllowed = 29,
+    NoPendingAdmin = 30,
+    AlreadyMigrated = 31,
+    Paused = 32,
+    BatchTooLarge = 33,
+    ClaimWindowClosed = 34,
+    ClaimWindowOpen = 35,
+    InsufficientPool = 36,
+    Overflow = 37,
+    MigrationPending = 38,
+    InvalidLiveness = 39,
+    RefundsPending = 40,
 }
@@ -0,0 +1,544 @@
+//! Typed payloads for every event the contract publishes.
+//!
+//! Topics keep their short symbol and the ids worth filtering on; the data is
+//! one of the structs below. Market lifecycle payloads carry the market state
+//! as it stands after the call so an indexer can follow a market from events
+//! alone. Every payload starts with `version`, bumped whenever a payload's
+//! fields change.
+
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Symbol, Vec};
+
+use crate::types::{CpmmPool, Market, MarketKind, MarketStatus, Proposal, Role, Side};
+
+pub const EVENT_VERSION: u32 = 2;
+
+/// Topics `("create", market_id)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketCreated {
+    pub version: u32,
+    pub market_id: u64,
+    pub creator: Address,
+    pub resolver: Option<Address>,
+    pub question: String,
+    pub deadline: u64,
+    pub trading_closes_at: u64,
+    pub kind: MarketKind,
+    pub token: Address,
+    pub creator_fee_bps: u32,
+    /// The LMSR subsidy, or zero.
+    pub pool_balance: i128,
+}
+
+/// Topics `("buy" | "sell", market_id, trader)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trade {
+    pub version: u32,
+    pub market_id: u64,
+    pub trader: Address,
+    pub side: Side,
+    /// Collateral paid in, fees included, or paid out on a sale.
+    pub amount: i128,
+    pub shares: i128,
+    pub total_yes: i128,
+    pub total_no: i128,
+    pub outcome_totals: Vec<i128>,
+    pub pool_balance: i128,
+}
+
+/// Topics `("add_liq" | "rem_liq", market_id, provider)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidityChanged {
+    pub version: u32,
+    pub market_id: u64,
+    pub provider: Address,
+    /// Collateral deposited or withdrawn.
+    pub amount: i128,
+    /// LP shares minted or burned.
+    pub lp_shares: i128,
+    pub yes_reserve: i128,
+    pub no_reserve: i128,
+    pub lp_supply: i128,
+    pub total_yes: i128,
+    pub total_no: i128,
+    pub pool_balance: i128,
+}
+
+/// Topics `("resolve" | "invalid", market_id)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketSettled {
+    pub version: u32,
+    pub market_id: u64,
+    pub status: MarketStatus,
+    pub total_yes: i128,
+    pub total_no: i128,
+    pub outcome_totals: Vec<i128>,
+    pub pool_balance: i128,
+}
+
+/// Topics `("sweep", market_id)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketSwept {
+    pub version: u32,
+    pub market_id: u64,
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// Topics `("surplus", market_id)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurplusWithdrawn {
+    pub version: u32,
+    pub market_id: u64,
+    pub creator: Address,
+    pub amount: i128,
+}
+
+/// Topics `("propose", market_id, proposer)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutcomeProposed {
+    pub version: u32,
+    pub market_id: u64,
+    pub proposer: Address,
+    pub outcome: Side,
+    pub bond: i128,
+    pub expires_at: u64,
+}
+
+/// Topics `("dispute", market_id, disputer)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProposalDisputed {
+    pub version: u32,
+    pub market_id: u64,
+    pub disputer: Address,
+    pub bond: i128,
+}
+
+/// Topics `("claim", market_id, user)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Claimed {
+    pub version: u32,
+    pub market_id: u64,
+    pub user: Address,
+    pub payout: i128,
+}
+
+/// Topics `("resolver", market_id)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolverChanged {
+    pub version: u32,
+    pub market_id: u64,
+    pub resolver: Address,
+}
+
+/// Topics `("upgrade",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upgraded {
+    pub version: u32,
+    pub wasm_hash: BytesN<32>,
+}
+
+/// Topics `("migrate",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Migrated {
+    pub version: u32,
+    pub from_schema: u32,
+    pub to_schema: u32,
+}
+
+/// Topics `("pause" | "unpause")` for the whole contract, or
+/// `("pause" | "unpause", market_id)` for one market.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauseChanged {
+    pub version: u32,
+    /// `None` for a global pause.
+    pub market_id: Option<u64>,
+    pub caller: Address,
+    pub paused: bool,
+}
+
+/// Topics `("grant" | "revoke", account)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoleChanged {
+    pub version: u32,
+    pub role: Role,
+    pub account: Address,
+    pub granted: bool,
+}
+
+/// Topics `("adm_prop" | "adm_cncl" | "admin", admin)`: a nomination made or
+/// withdrawn, or a completed transfer. `admin` is the admin before the call.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminTransfer {
+    pub version: u32,
+    pub admin: Address,
+    pub new_admin: Address,
+}
+
+/// Topics `("withdraw", to, token)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeesWithdrawn {
+    pub version: u32,
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// Topics `("token", token)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenAllowed {
+    pub version: u32,
+    pub token: Address,
+    pub allowed: bool,
+}
+
+/// Topics `("fee",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProtocolFeeChanged {
+    pub version: u32,
+    pub caller: Address,
+    pub fee_bps: u32,
+}
+
+/// Topics `("disp_prm",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisputeParamsChanged {
+    pub version: u32,
+    pub bond: i128,
+    pub liveness: u64,
+}
+
+/// Topics `("claim_win",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimWindowChanged {
+    pub version: u32,
+    pub window: u64,
+}
+
+/// Topics `("creation",)`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreationChanged {
+    pub version: u32,
+    pub permissionless: bool,
+}
+
+pub fn market_created(env: &Env, market_id: u64, market: &Market) {
+    env.events().publish(
+        (symbol_short!("create"), market_id),
+        MarketCreated {
+            version: EVENT_VERSION,
+            market_id,
+            creator: market.creator.clone(),
+            resolver: market.resolver.clone(),
+            question: market.question.clone(),
+            deadline: market.deadline,
+            trading_closes_at: market.trading_closes_at,
+            kind: market.kind.clone(),
+            token: market.token.clone(),
+            creator_fee_bps: market.creator_fee_bps,
+            pool_balance: market.pool_balance,
+        },
+    );
+}
+
+pub fn trade(
+    env: &Env,
+    topic: Symbol,
+    market_id: u64,
+    trader: &Address,
+    side: &Side,
+    amount: i128,
+    shares: i128,
+    market: &Market,
+) {
+    env.events().publish(
+        (topic, market_id, trader.clone()),
+        Trade {
+            version: EVENT_VERSION,
+            market_id,
+            trader: trader.clone(),
+            side: side.clone(),
+            amount,
+            shares,
+            total_yes: market.total_yes,
+            total_no: market.total_no,
+            outcome_totals: market.outcome_totals.clone(),
+            pool_balance: market.pool_balance,
+        },
+    );
+}
+
+pub fn liquidity_changed(
+    env: &Env,
+    topic: Symbol,
+    market_id: u64,
+    provider: &Address,
+    amount: i128,
+    lp_shares: i128,
+    pool: &CpmmPool,
+    market: &Market,
+) {
+    env.events().publish(
+        (topic, market_id, provider.clone()),
+        LiquidityChanged {
+            version: EVENT_VERSION,
+            market_id,
+            provider: provider.clone(),
+            amount,
+            lp_shares,
+            yes_reserve: pool.yes_reserve,
+            no_reserve: pool.no_reserve,
+            lp_supply: pool.lp_supply,
+            total_yes: market.total_yes,
+            total_no: market.total_no,
+            pool_balance: market.pool_balance,
+        },
+    );
+}
+
+/// Published once a market is resolved or invalidated, under `"invalid"` for
+/// the latter and `"resolve"` otherwise.
+pub fn market_settled(env: &Env, market_id: u64, market: &Market) {
+    let topic = if market.status == MarketStatus::Invalid {
+        symbol_short!("invalid")
+    } else {
+        symbol_short!("resolve")
+    };
+    env.events().publish(
+        (topic, market_id),
+        MarketSettled {
+            version: EVENT_VERSION,
+            market_id,
+            status: market.status.clone(),
+            total_yes: market.total_yes,
+            total_no: market.total_no,
+            outcome_totals: market.outcome_totals.clone(),
+            pool_balance: market.pool_balance,
+        },
+    );
+}
+
+pub fn outcome_proposed(env: &Env, market_id: u64, proposal: &Proposal) {
+    env.events().publish(
+        (symbol_short!("propose"), market_id, proposal.proposer.clone()),
+        OutcomeProposed {
+            version: EVENT_VERSION,
+            market_id,
+            proposer: proposal.proposer.clone(),
+            outcome: proposal.outcome.clone(),
+            bond: proposal.bond,
+            expires_at: proposal.expires_at,
+        },
+    );
+}
+
+pub fn proposal_disputed(env: &Env, market_id: u64, disputer: &Address, bond: i128) {
+    env.events().publish(
+        (symbol_short!("dispute"), market_id, disputer.clone()),
+        ProposalDisputed {
+            version: EVENT_VERSION,
+            market_id,
+            disputer: disputer.clone(),
+            bond,
+        },
+    );
+}
+
+pub fn claimed(env: &Env, market_id: u64, user: &Address, payout: i128) {
+    env.events().publish(
+        (symbol_short!("claim"), market_id, user.clone()),
+        Claimed {
+            version: EVENT_VERSION,
+            market_id,
+            user: user.clone(),
+            payout,
+        },
+    );
+}
+
+pub fn market_swept(env: &Env, market_id: u64, to: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("sweep"), market_id),
+        MarketSwept {
+            version: EVENT_VERSION,
+            market_id,
+            to: to.clone(),
+            amount,
+        },
+    );
+}
+
+pub fn surplus_withdrawn(env: &Env, market_id: u64, creator: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("surplus"), market_id),
+        SurplusWithdrawn {
+            version: EVENT_VERSION,
+            market_id,
+            creator: creator.clone(),
+            amount,
+        },
+    );
+}
+
+pub fn resolver_changed(env: &Env, market_id: u64, resolver: &Address) {
+    env.events().publish(
+        (symbol_short!("resolver"), market_id),
+        ResolverChanged {
+            version: EVENT_VERSION,
+            market_id,
+            resolver: resolver.clone(),
+        },
+    );
+}
+
+pub fn upgraded(env: &Env, wasm_hash: &BytesN<32>) {
+    env.events().publish(
+        (symbol_short!("upgrade"),),
+        Upgraded {
+            version: EVENT_VERSION,
+            wasm_hash: wasm_hash.clone(),
+        },
+    );
+}
+
+pub fn migrated(env: &Env, from_schema: u32, to_schema: u32) {
+    env.events().publish(
+        (symbol_short!("migrate"),),
+        Migrated {
+            version: EVENT_VERSION,
+            from_schema,
+            to_schema,
+        },
+    );
+}
+
+pub fn pause_changed(env: &Env, market_id: Option<u64>, caller: &Address, paused: bool) {
+    let topic = if paused {
+        symbol_short!("pause")
+    } else {
+        symbol_short!("unpause")
+    };
+    let payload = PauseChanged {
+        version: EVENT_VERSION,
+        market_id,
+        caller: caller.clone(),
+        paused,
+    };
+    match market_id {
+        Some(market_id) => env.events().publish((topic, market_id), payload),
+        None => env.events().publish((topic,), payload),
+    }
+}
+
+pub fn role_changed(env: &Env, role: &Role, account: &Address, granted: bool) {
+    let topic = if granted {
+        symbol_short!("grant")
+    } else {
+        symbol_short!("revoke")
+    };
+    env.events().publish(
+        (topic, account.clone()),
+        RoleChanged {
+            version: EVENT_VERSION,
+            role: role.clone(),
+            account: account.clone(),
+            granted,
+        },
+    );
+}
+
+pub fn admin_transfer(env: &Env, topic: Symbol, admin: &Address, new_admin: &Address) {
+    env.events().publish(
+        (topic, admin.clone()),
+        AdminTransfer {
+            version: EVENT_VERSION,
+            admin: admin.clone(),
+            new_admin: new_admin.clone(),
+        },
+    );
+}
+
+pub fn fees_withdrawn(env: &Env, token: &Address, to: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("withdraw"), to.clone(), token.clone()),
+        FeesWithdrawn {
+            version: EVENT_VERSION,
+            token: token.clone(),
+            to: to.clone(),
+            amount,
+        },
+    );
+}
+
+pub fn token_allowed(env: &Env, token: &Address, allowed: bool) {
+    env.events().publish(
+        (symbol_short!("token"), token.clone()),
+        TokenAllowed {
+            version: EVENT_VERSION,
+            token: token.clone(),
+            allowed,
+        },
+    );
+}
+
+pub fn protocol_fee_changed(env: &Env, caller: &Address, fee_bps: u32) {
+    env.events().publish(
+        (symbol_short!("fee"),),
+        ProtocolFeeChanged {
+            version: EVENT_VERSION,
+            caller: caller.clone(),
+            fee_bps,
+        },
+    );
+}
+
+pub fn dispute_params_changed(env: &Env, bond: i128, liveness: u64) {
+    env.events().publish(
+        (symbol_short!("disp_prm"),),
+        DisputeParamsChanged {
+            version: EVENT_VERSION,
+            bond,
+            liveness,
+        },
+    );
+}
+
+pub fn claim_window_changed(env: &Env, window: u64) {
+    env.events().publish(
+        (symbol_short!("claim_win"),),
+        ClaimWindowChanged {
+            version: EVENT_VERSION,
+            window,
+        },
+    );
+}
+
+pub fn creation_changed(env: &Env, permissionless: bool) {
+    env.events().publish(
+        (symbol_short!("creation"),),
+        CreationChanged {
+            version: EVENT_VERSION,
+            permissionless,
+        },
+    );
+}
@@ -1,17 +1,48 @@
 #![no_std]
+// Entry points mirror the on-chain ABI, which allows up to 10 parameters.
+#![allow(clippy::too_many_arguments)]
 
+mod cpmm;
 mod errors;
+mod events;
+mod lmsr;
+mod math;
+mod oracle;
 mod types;
 
 #[cfg(test)]
 mod test;
 
 use errors::Error;
-use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, String};
-use types::{DataKey, Market, MarketStatus, Position, Side};
+use oracle::{Asset, OracleClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, Address, BytesN, Env, Map, String, Vec,
+};
+use types::{
+    ClaimResult, ClaimStatus, Comparison, CpmmPool, DataKey, Market, MarketIndex, MarketKind,
+    MarketListing, MarketStatus, MarketV2, MarketV3, OracleSpec, Position, Proposal, Role, Side,
+    StatusFilter, TokenSolvency, UserPosition,
+};
+
+/// Storage layout version written by this build. Bump it whenever the
+/// persisted shape of `Market` or `Position` changes, and add the matching
+/// step to `migrate`.
+const SCHEMA_VERSION: u32 = 4;
 
 const BUMP_THRESHOLD: u32 = 17_280; // ~1 day in ledgers
 const EXTEND_TO: u32 = 518_400; // ~30 days in ledgers
+const DEFAULT_PROPOSAL_BOND: i128 = 100_000_000; // 10 XLM
+const DEFAULT_LIVENESS: u64 = 7_200; // 2 hours
+const MAX_LIVENESS: u64 = 2_592_000; // 30 days
+const DEFAULT_CLAIM_WINDOW: u64 = 31_536_000; // 365 days
+const MAX_FEE_BPS: u32 = 1_000; // 10%
+const BPS_DENOMINATOR: i128 = 10_000;
+const MAX_PAGE_SIZE: u32 = 50;
+/// Each claimed market rewrites its market and position, so this keeps a
+/// full batch inside the per-transaction write limit.
+const MAX_CLAIM_BATCH: u32 = 8;
+const MIN_OUTCOMES: u32 = 3;
+const MAX_OUTCOMES: u32 = 16;
 
 fn bump_instance(env: &Env) {
     env.storage().instance().extend_ttl(BUMP_THRESHOLD, EXTEND_TO);
@@ -23,21 +54,107 @@ fn bump_persistent(env: &Env, key: &DataKey) {
         .extend_ttl(key, BUMP_THRESHOLD, EXTEND_TO);
 }
 
-fn get_admin(env: &Env) -> Address {
+fn get_admin(env: &Env) -> Result<Address, Error> {
     env.storage()
         .instance()
         .get(&DataKey::Admin)
-        .expect("not initialized")
+        .ok_or(Error::NotInitialized)
+}
+
+fn require_admin(env: &Env) -> Result<Address, Error> {
+    let admin = get_admin(env)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+fn has_role(env: &Env, role: &Role, account: &Address) -> Result<bool, Error> {
+    if *account == get_admin(env)? {
+        return Ok(true);
+    }
+    let key = DataKey::Role(role.clone(), account.clone());
+    let granted = env.storage().persistent().get(&key).unwrap_or(false);
+    if granted {
+        bump_persistent(env, &key);
+    }
+    Ok(granted)
+}
+
+/// Authenticates `caller` and checks that it holds `role`.
+fn require_role(env: &Env, caller: &Address, role: Role) -> Result<(), Error> {
+    caller.require_auth();
+    if has_role(env, &role, caller)? {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+/// Authenticates `caller` and checks that it may resolve `market`: either
+/// the market's own resolver (the current admin if none was set) or a holder
+/// of the `Resolver` role.
+fn require_resolver(env: &Env, caller: &Address, market: &Market) -> Result<(), Error> {
+    caller.require_auth();
+    let resolver = match &market.resolver {
+        Some(resolver) => resolver.clone(),
+        None => get_admin(env)?,
+    };
+    if *caller == resolver || has_role(env, &Role::Resolver, caller)? {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
 }
 
-fn get_token(env: &Env) -> Address {
+fn get_token(env: &Env) -> Result<Address, Error> {
     env.storage()
         .instance()
         .get(&DataKey::Token)
-        .expect("not initialized")
+        .ok_or(Error::NotInitialized)
+}
+
+/// Deployments from before the version was stored hold version 1 data.
+fn get_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(1)
+}
+
+fn is_frozen(env: &Env, market_id: u64) -> bool {
+    let key = DataKey::Frozen(market_id);
+    let frozen = env.storage().persistent().get(&key).unwrap_or(false);
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
+    }
+    frozen
+}
+
+/// Seconds of ledger time during which the contract was not paused. Dispute
+/// and claim windows run on this clock, so a pause suspends them.
+fn clock(env: &Env) -> u64 {
+    let storage = env.storage().instance();
+    let paused_total: u64 = storage.get(&DataKey::PausedTotal).unwrap_or(0);
+    let now = storage
+        .get(&DataKey::PausedAt)
+        .unwrap_or(env.ledger().timestamp());
+    now.saturating_sub(paused_total)
+}
+
+/// Fails if the contract is paused or the market is frozen.
+fn check_not_paused(env: &Env, market_id: u64) -> Result<(), Error> {
+    let paused = env
+        .storage()
+        .instance()
+        .get(&DataKey::Paused)
+        .unwrap_or(false);
+    if paused || is_frozen(env, market_id) {
+        return Err(Error::Paused);
+    }
+    Ok(())
 }
 
 fn get_market(env: &Env, market_id: u64) -> Result<Market, Error> {
+    check_migrated(env)?;
     let key = DataKey::Market(market_id);
     let market: Market = env
         .storage()
@@ -48,6 +165,31 @@ fn get_market(env: &Env, market_id: u64) -> Result<Market, Error> {
     Ok(market)
 }
 
+/// Markets can only be read in this build's layout once `migrate` is done.
+fn check_migrated(env: &Env) -> Result<(), Error> {
+    // A contract never initialized has nothing to migrate
+    let initialized = env.storage().instance().has(&DataKey::Admin);
+    if initialized && get_schema_version(env) < SCHEMA_VERSION {
+        Err(Error::MigrationPending)
+    } else {
+        Ok(())
+    }
+}
+
+fn get_market_v2(env: &Env, market_id: u64) -> Result<MarketV2, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Market(market_id))
+        .ok_or(Error::MarketNotFound)
+}
+
+fn get_market_v3(env: &Env, market_id: u64) -> Result<MarketV3, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Market(market_id))
+        .ok_or(Error::MarketNotFound)
+}
+
 fn save_market(env: &Env, market_id: u64, market: &Market) {
     let key = DataKey::Market(market_id);
     env.storage().persistent().set(&key, market);
@@ -59,6 +201,7 @@ fn get_position(env: &Env, market_id: u64, user: &Address) -> Position {
     let pos = env.storage().persistent().get(&key).unwrap_or(Position {
         yes_shares: 0,
         no_shares: 0,
+        outcome_shares: Vec::new(env),
         claimed: false,
     });
     if env.storage().persistent().has(&key) {
@@ -69,228 +212,2465 @@ fn get_position(env: &Env, market_id: u64, user: &Address) -> Position {
 
 fn save_position(env: &Env, market_id: u64, user: &Address, position: &Position) {
     let key = DataKey::Position(market_id, user.clone());
+    if !env.storage().persistent().has(&key) {
+        index_push(env, &MarketIndex::User(user.clone()), market_id);
+    }
     env.storage().persistent().set(&key, position);
     bump_persistent(env, &key);
 }
 
-fn get_next_market_id(env: &Env) -> u64 {
-    env.storage()
-        .instance()
-        .get(&DataKey::NextMarketId)
-        .unwrap_or(0)
+fn get_pool(env: &Env, market_id: u64) -> CpmmPool {
+    let key = DataKey::Pool(market_id);
+    let pool = env.storage().persistent().get(&key).unwrap_or(CpmmPool {
+        yes_reserve: 0,
+        no_reserve: 0,
+        lp_supply: 0,
+    });
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
+    }
+    pool
 }
 
-#[contract]
-pub struct PredictionMarket;
+fn save_pool(env: &Env, market_id: u64, pool: &CpmmPool) {
+    let key = DataKey::Pool(market_id);
+    env.storage().persistent().set(&key, pool);
+    bump_persistent(env, &key);
+}
 
-#[contractimpl]
-impl PredictionMarket {
-    /// Initialize the contract with admin address and XLM SAC token address.
-    /// Can only be called once.
-    pub fn initialize(env: Env, admin: Address, token: Address) -> Result<(), Error> {
-        if env.storage().instance().has(&DataKey::Admin) {
-            return Err(Error::AlreadyInitialized);
-        }
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        env.storage().instance().set(&DataKey::Token, &token);
-        env.storage().instance().set(&DataKey::NextMarketId, &0u64);
-        bump_instance(&env);
-        Ok(())
+fn get_lp_shares(env: &Env, market_id: u64, provider: &Address) -> i128 {
+    let key = DataKey::LpShares(market_id, provider.clone());
+    let shares = env.storage().persistent().get(&key).unwrap_or(0);
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
     }
+    shares
+}
 
-    /// Create a new prediction market. Returns the market ID.
-    pub fn create_market(
-        env: Env,
-        creator: Address,
-        question: String,
-        deadline: u64,
-    ) -> Result<u64, Error> {
-        creator.require_auth();
-        bump_instance(&env);
+fn save_lp_shares(env: &Env, market_id: u64, provider: &Address, shares: i128) {
+    let key = DataKey::LpShares(market_id, provider.clone());
+    env.storage().persistent().set(&key, &shares);
+    bump_persistent(env, &key);
+}
 
-        let now = env.ledger().timestamp();
-        if deadline <= now {
-            return Err(Error::InvalidDeadline);
-        }
+/// Whether a market's refunds come from cost bases rather than shares.
+fn tracks_cost_basis(market: &Market) -> bool {
+    matches!(market.kind, MarketKind::Lmsr(_) | MarketKind::Cpmm)
+}
 
-        let market_id = get_next_market_id(&env);
-        let market = Market {
-            creator: creator.clone(),
-            question,
-            deadline,
-            status: MarketStatus::Open,
-            total_yes: 0,
-            total_no: 0,
-            pool_balance: 0,
-        };
+/// `None` for accounts that never traded the market, or only traded it
+/// before cost bases were recorded.
+fn get_cost_basis(env: &Env, market_id: u64, account: &Address) -> Option<i128> {
+    let key = DataKey::CostBasis(market_id, account.clone());
+    let basis = env.storage().persistent().get(&key);
+    if basis.is_some() {
+        bump_persistent(env, &key);
+    }
+    basis
+}
 
-        save_market(&env, market_id, &market);
+fn get_cost_basis_total(env: &Env, market_id: u64) -> i128 {
+    let key = DataKey::CostBasisTotal(market_id);
+    let total = env.storage().persistent().get(&key).unwrap_or(0);
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
+    }
+    total
+}
+
+fn save_cost_basis_total(env: &Env, market_id: u64, total: i128) {
+    let key = DataKey::CostBasisTotal(market_id);
+    env.storage().persistent().set(&key, &total);
+    bump_persistent(env, &key);
+}
+
+/// Moves `account`'s cost basis in an LMSR or CPMM market by `delta`. It
+/// never drops below zero: an account that took out more than it put in is
+/// owed nothing back.
+fn add_cost_basis(
+    env: &Env,
+    market: &Market,
+    market_id: u64,
+    account: &Address,
+    delta: i128,
+) -> Result<(), Error> {
+    if !tracks_cost_basis(market) {
+        return Ok(());
+    }
+    let basis = get_cost_basis(env, market_id, account).unwrap_or(0);
+    let updated = math::add(basis, delta)?.max(0);
+    let total = math::add(get_cost_basis_total(env, market_id), math::sub(updated, basis)?)?;
+    let key = DataKey::CostBasis(market_id, account.clone());
+    env.storage().persistent().set(&key, &updated);
+    bump_persistent(env, &key);
+    save_cost_basis_total(env, market_id, total);
+    Ok(())
+}
+
+/// Clears `account`'s cost basis once it has been refunded.
+fn take_cost_basis(env: &Env, market_id: u64, account: &Address) -> Result<(), Error> {
+    if let Some(basis) = get_cost_basis(env, market_id, account) {
         env.storage()
-            .instance()
-            .set(&DataKey::NextMarketId, &(market_id + 1));
+            .persistent()
+            .remove(&DataKey::CostBasis(market_id, account.clone()));
+        let total = math::sub(get_cost_basis_total(env, market_id), basis)?;
+        save_cost_basis_total(env, market_id, total);
+    }
+    Ok(())
+}
 
-        env.events()
-            .publish((symbol_short!("create"), market_id), creator);
+fn get_oracle(env: &Env, market_id: u64) -> Option<OracleSpec> {
+    let key = DataKey::Oracle(market_id);
+    let spec = env.storage().persistent().get(&key);
+    if spec.is_some() {
+        bump_persistent(env, &key);
+    }
+    spec
+}
 
-        Ok(market_id)
+fn get_proposal(env: &Env, market_id: u64) -> Option<Proposal> {
+    let key = DataKey::Proposal(market_id);
+    let proposal = env.storage().persistent().get(&key);
+    if proposal.is_some() {
+        bump_persistent(env, &key);
     }
+    proposal
+}
 
-    /// Buy YES or NO shares for a market. Amount is in stroops (1 XLM = 10_000_000).
-    pub fn buy_shares(
-        env: Env,
-        buyer: Address,
-        market_id: u64,
-        side: Side,
-        amount: i128,
-    ) -> Result<(), Error> {
-        buyer.require_auth();
-        bump_instance(&env);
+fn save_proposal(env: &Env, market_id: u64, proposal: &Proposal) {
+    let key = DataKey::Proposal(market_id);
+    env.storage().persistent().set(&key, proposal);
+    bump_persistent(env, &key);
+}
 
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
+/// Fails unless the market is still open with no outcome proposed.
+fn check_unresolved(market: &Market) -> Result<(), Error> {
+    match market.status {
+        MarketStatus::Open => Ok(()),
+        MarketStatus::Proposed | MarketStatus::Disputed => Err(Error::ProposalPending),
+        _ => Err(Error::MarketAlreadyResolved),
+    }
+}
 
-        let mut market = get_market(&env, market_id)?;
+/// Checks that `side` names one of the market's outcomes: YES/NO for binary
+/// markets, an index into the labels for categorical ones.
+fn check_side(market: &Market, side: &Side) -> Result<(), Error> {
+    let valid = match (&market.kind, side) {
+        (MarketKind::Categorical(labels), Side::Outcome(i)) => *i < labels.len(),
+        (MarketKind::Categorical(_), _) | (_, Side::Outcome(_)) => false,
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidOutcome)
+    }
+}
 
-        if market.status != MarketStatus::Open {
-            return Err(Error::MarketClosed);
-        }
+fn market_shares(market: &Market, side: &Side) -> i128 {
+    match side {
+        Side::Yes => market.total_yes,
+        Side::No => market.total_no,
+        Side::Outcome(i) => market.outcome_totals.get(*i).unwrap_or(0),
+    }
+}
 
-        let now = env.ledger().timestamp();
-        if now >= market.deadline {
-            return Err(Error::MarketClosed);
-        }
+fn position_shares(position: &Position, side: &Side) -> i128 {
+    match side {
+        Side::Yes => position.yes_shares,
+        Side::No => position.no_shares,
+        Side::Outcome(i) => position.outcome_shares.get(*i).unwrap_or(0),
+    }
+}
 
-        // Transfer XLM from buyer to contract
-        let token_address = get_token(&env);
-        let token_client = token::Client::new(&env, &token_address);
-        token_client.transfer(&buyer, &env.current_contract_address(), &amount);
+/// Every share a position holds, across all sides.
+fn position_total(position: &Position) -> Result<i128, Error> {
+    let binary = math::add(position.yes_shares, position.no_shares)?;
+    math::add(binary, math::sum(position.outcome_shares.iter())?)
+}
 
-        // Update market totals
-        match side {
-            Side::Yes => market.total_yes += amount,
-            Side::No => market.total_no += amount,
-        }
-        market.pool_balance += amount;
+/// Outstanding shares of a binary market as `(side, other side)`.
+fn binary_totals(market: &Market, side: &Side) -> (i128, i128) {
+    if *side == Side::Yes {
+        (market.total_yes, market.total_no)
+    } else {
+        (market.total_no, market.total_yes)
+    }
+}
 
-        // Update user position
-        let mut position = get_position(&env, market_id, &buyer);
-        match side {
-            Side::Yes => position.yes_shares += amount,
-            Side::No => position.no_shares += amount,
+/// Adds `delta` shares of `side` to both the market totals and the position.
+fn credit_shares(
+    market: &mut Market,
+    position: &mut Position,
+    side: &Side,
+    delta: i128,
+) -> Result<(), Error> {
+    match side {
+        Side::Yes => {
+            market.total_yes = math::add(market.total_yes, delta)?;
+            position.yes_shares = math::add(position.yes_shares, delta)?;
         }
+        Side::No => {
+            market.total_no = math::add(market.total_no, delta)?;
+            position.no_shares = math::add(position.no_shares, delta)?;
+        }
+        Side::Outcome(i) => {
+            let i = *i;
+            while position.outcome_shares.len() < market.outcome_totals.len() {
+                position.outcome_shares.push_back(0);
+            }
+            let total = math::add(market.outcome_totals.get_unchecked(i), delta)?;
+            let shares = math::add(position.outcome_shares.get_unchecked(i), delta)?;
+            market.outcome_totals.set(i, total);
+            position.outcome_shares.set(i, shares);
+        }
+    }
+    Ok(())
+}
 
-        save_market(&env, market_id, &market);
-        save_position(&env, market_id, &buyer, &position);
-
-        env.events().publish(
-            (symbol_short!("buy"), market_id, buyer),
-            (side, amount),
-        );
+/// Protocol and creator fees taken off a buy of `amount`.
+fn buy_fees(env: &Env, market: &Market, amount: i128) -> Result<(i128, i128), Error> {
+    let protocol_bps = get_protocol_fee_bps(env) as i128;
+    let protocol_fee = math::mul_div(env, amount, protocol_bps, BPS_DENOMINATOR)?;
+    let creator_bps = market.creator_fee_bps as i128;
+    let creator_fee = math::mul_div(env, amount, creator_bps, BPS_DENOMINATOR)?;
+    Ok((protocol_fee, creator_fee))
+}
 
-        Ok(())
+/// Shares issued for a net stake of `net` on `side`. For CPMM markets the
+/// updated pool is returned as well, for the caller to save.
+fn shares_for_buy(
+    env: &Env,
+    market: &Market,
+    market_id: u64,
+    side: &Side,
+    net: i128,
+) -> Result<(i128, Option<CpmmPool>), Error> {
+    match &market.kind {
+        MarketKind::Parimutuel | MarketKind::Categorical(_) | MarketKind::Scalar(_) => {
+            Ok((net, None))
+        }
+        MarketKind::Lmsr(b) => {
+            let (q_self, q_other) = binary_totals(market, side);
+            Ok((lmsr::shares_for_cost(*b, q_self, q_other, net)?, None))
+        }
+        MarketKind::Cpmm => {
+            let mut pool = get_pool(env, market_id);
+            if pool.lp_supply == 0 {
+                return Err(Error::NoLiquidity);
+            }
+            let shares = cpmm::buy(&mut pool, side, net)?;
+            Ok((shares, Some(pool)))
+        }
     }
+}
 
-    /// Resolve a market. Only admin can call. Deadline must have passed.
-    pub fn resolve_market(env: Env, market_id: u64, outcome: Side) -> Result<(), Error> {
-        let admin = get_admin(&env);
-        admin.require_auth();
-        bump_instance(&env);
+/// Amount `claim_winnings` pays `user` for a position in a settled market.
+fn payout_for(
+    env: &Env,
+    market: &Market,
+    market_id: u64,
+    user: &Address,
+    position: &Position,
+) -> Result<i128, Error> {
+    let winning_side = match &market.status {
+        MarketStatus::Resolved(side) => side,
+        MarketStatus::ResolvedScalar(value) => {
+            return scalar_payout(env, market, position, *value)
+        }
+        MarketStatus::Invalid => return refund_for(env, market, market_id, user, position),
+        MarketStatus::Open | MarketStatus::Proposed | MarketStatus::Disputed => {
+            return Err(Error::MarketNotResolved)
+        }
+    };
 
-        let mut market = get_market(&env, market_id)?;
+    let user_winning_shares = position_shares(position, winning_side);
+    let total_winning_shares = market_shares(market, winning_side);
 
-        if market.status != MarketStatus::Open {
-            return Err(Error::MarketAlreadyResolved);
+    let payout = if let MarketKind::Lmsr(_) | MarketKind::Cpmm = market.kind {
+        // Each winning share redeems for exactly 1 stroop
+        user_winning_shares
+    } else if total_winning_shares == 0 {
+        // No winners: refund proportionally
+        let user_total = position_total(position)?;
+        let binary_shares = math::add(market.total_yes, market.total_no)?;
+        let total_shares = math::add(binary_shares, math::sum(market.outcome_totals.iter())?)?;
+        if total_shares == 0 {
+            0
+        } else {
+            math::mul_div(env, user_total, market.pool_balance, total_shares)?
         }
+    } else if user_winning_shares == 0 {
+        0
+    } else {
+        math::mul_div(env, user_winning_shares, market.pool_balance, total_winning_shares)?
+    };
+    Ok(payout)
+}
 
-        let now = env.ledger().timestamp();
-        if now < market.deadline {
-            return Err(Error::DeadlineNotReached);
+/// Implied probability of each side in basis points: YES then NO for binary
+/// and scalar markets, one entry per outcome for categorical ones. Stake-based
+/// markets weigh sides by their share of the stakes; LMSR and CPMM markets
+/// use the market maker's marginal price. Empty markets split evenly. The
+/// entries always sum to 10 000.
+fn odds_bps(env: &Env, market: &Market, market_id: u64) -> Result<Vec<u32>, Error> {
+    let bps = BPS_DENOMINATOR as u32;
+    let yes = match &market.kind {
+        MarketKind::Categorical(_) => {
+            let count = market.outcome_totals.len();
+            let total = math::sum(market.outcome_totals.iter())?;
+            let mut odds = Vec::new(env);
+            for staked in market.outcome_totals.iter() {
+                odds.push_back(if total == 0 {
+                    bps / count
+                } else {
+                    math::mul_div(env, staked, BPS_DENOMINATOR, total)? as u32
+                });
+            }
+            // Rounding down leaves a few basis points over; the first
+            // outcome takes them so the odds always add up to 100%
+            let assigned: u32 = odds.iter().sum();
+            odds.set(0, odds.get_unchecked(0) + (bps - assigned));
+            return Ok(odds);
+        }
+        MarketKind::Parimutuel | MarketKind::Scalar(_) => {
+            let total = math::add(market.total_yes, market.total_no)?;
+            if total == 0 {
+                bps / 2
+            } else {
+                math::mul_div(env, market.total_yes, BPS_DENOMINATOR, total)? as u32
+            }
+        }
+        MarketKind::Lmsr(b) => lmsr::price_bps(*b, market.total_yes, market.total_no)?,
+        MarketKind::Cpmm => {
+            let pool = get_pool(env, market_id);
+            let reserves = math::add(pool.yes_reserve, pool.no_reserve)?;
+            if reserves == 0 {
+                bps / 2
+            } else {
+                // The scarcer side's reserve is the pricier one
+                math::mul_div(env, pool.no_reserve, BPS_DENOMINATOR, reserves)? as u32
+            }
         }
+    };
+    let mut odds = Vec::new(env);
+    odds.push_back(yes);
+    odds.push_back(bps - yes);
+    Ok(odds)
+}
 
-        market.status = MarketStatus::Resolved(outcome.clone());
-        save_market(&env, market_id, &market);
+fn get_claim_window(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ClaimWindow)
+        .unwrap_or(DEFAULT_CLAIM_WINDOW)
+}
 
-        env.events()
-            .publish((symbol_short!("resolve"), market_id), outcome);
+fn is_settled(market: &Market) -> bool {
+    !matches!(
+        market.status,
+        MarketStatus::Open | MarketStatus::Proposed | MarketStatus::Disputed
+    )
+}
 
-        Ok(())
-    }
+/// Whether a settled market's claim window has run out. A window reaching
+/// past the end of time never closes.
+fn claim_window_closed(env: &Env, market: &Market) -> bool {
+    is_settled(market)
+        && market.claim_window > 0
+        && clock(env) >= market.settled_at.saturating_add(market.claim_window)
+}
 
-    /// Claim winnings from a resolved market.
-    pub fn claim_winnings(env: Env, user: Address, market_id: u64) -> Result<i128, Error> {
-        user.require_auth();
-        bump_instance(&env);
+/// Fails if claims on `market` can't be paid right now: before settlement,
+/// while paused or frozen, and after the claim window closes. Refunds from
+/// invalid markets stay open while paused.
+fn check_claims_open(env: &Env, market: &Market, market_id: u64) -> Result<(), Error> {
+    if market.status != MarketStatus::Invalid {
+        check_not_paused(env, market_id)?;
+    }
+    if !is_settled(market) {
+        return Err(Error::MarketNotResolved);
+    }
+    if claim_window_closed(env, market) {
+        return Err(Error::ClaimWindowClosed);
+    }
+    Ok(())
+}
 
-        let market = get_market(&env, market_id)?;
-        let mut position = get_position(&env, market_id, &user);
+/// What claiming would pay right now: zero wherever `claim_winnings` would
+/// refuse, and once claimed.
+fn claimable_for(
+    env: &Env,
+    market: &Market,
+    market_id: u64,
+    user: &Address,
+    position: &Position,
+) -> Result<i128, Error> {
+    if position.claimed || check_claims_open(env, market, market_id).is_err() {
+        Ok(0)
+    } else {
+        payout_for(env, market, market_id, user, position)
+    }
+}
 
-        if position.claimed {
-            return Err(Error::AlreadyClaimed);
-        }
+/// Adds `delta` to a market's pool, and to what the contract owes.
+fn add_to_pool(env: &Env, market: &mut Market, delta: i128) -> Result<(), Error> {
+    market.pool_balance = math::add(market.pool_balance, delta)?;
+    add_liabilities(env, &market.token, delta)
+}
 
-        let winning_side = match &market.status {
-            MarketStatus::Resolved(side) => side.clone(),
-            MarketStatus::Open => return Err(Error::MarketNotResolved),
-        };
+/// Books `amount` as paid out of a settled market's pool, refusing to pay
+/// more than the pool still holds.
+fn pay_from_pool(env: &Env, market: &mut Market, amount: i128) -> Result<(), Error> {
+    check_pool_covers(market, amount)?;
+    market.total_paid_out = math::add(market.total_paid_out, amount)?;
+    add_liabilities(env, &market.token, -amount)
+}
 
-        let user_winning_shares = match winning_side {
-            Side::Yes => position.yes_shares,
-            Side::No => position.no_shares,
-        };
+fn check_pool_covers(market: &Market, amount: i128) -> Result<(), Error> {
+    if amount > math::sub(market.pool_balance, market.total_paid_out)? {
+        Err(Error::InsufficientPool)
+    } else {
+        Ok(())
+    }
+}
 
-        let total_winning_shares = match winning_side {
-            Side::Yes => market.total_yes,
-            Side::No => market.total_no,
-        };
+/// Books a claim of `payout` by `user` against the market.
+fn record_claim(
+    env: &Env,
+    market: &mut Market,
+    market_id: u64,
+    user: &Address,
+    position: &Position,
+    payout: i128,
+) -> Result<(), Error> {
+    pay_from_pool(env, market, payout)?;
+    market.claimed_total = math::add(market.claimed_total, payout)?;
+    let redeemed = redeemed_shares(market, position)?;
+    market.claimed_winning_shares = math::add(market.claimed_winning_shares, redeemed)?;
+    if market.status == MarketStatus::Invalid {
+        take_cost_basis(env, market_id, user)?;
+    }
+    Ok(())
+}
 
-        // Calculate payout
-        let payout = if total_winning_shares == 0 {
-            // No winners: refund proportionally
-            let user_total = position.yes_shares + position.no_shares;
-            let total_shares = market.total_yes + market.total_no;
-            if total_shares == 0 {
-                0
+/// Shares a claim redeems: those on the winning side, or every share when
+/// the whole position is paid out (refunds, scalar markets and parimutuel
+/// markets without winners).
+fn redeemed_shares(market: &Market, position: &Position) -> Result<i128, Error> {
+    match &market.status {
+        MarketStatus::Resolved(side) => {
+            let stake_based = !matches!(market.kind, MarketKind::Lmsr(_) | MarketKind::Cpmm);
+            if stake_based && market_shares(market, side) == 0 {
+                position_total(position)
             } else {
-                (user_total * market.pool_balance) / total_shares
+                Ok(position_shares(position, side))
             }
-        } else if user_winning_shares == 0 {
-            0
-        } else {
-            (user_winning_shares * market.pool_balance) / total_winning_shares
-        };
+        }
+        _ => position_total(position),
+    }
+}
 
-        // Mark as claimed before transfer (checks-effects-interactions)
-        position.claimed = true;
-        save_position(&env, market_id, &user, &position);
+/// Refund owed to `user` in an invalid market. Stakes in 1:1 markets are
+/// returned in full. LMSR and CPMM accounts get back their cost basis,
+/// scaled down pro rata if traders who sold at a profit left the pool short
+/// of every basis. Positions from before cost bases were recorded redeem at
+/// half a stroop per share, as a YES/NO pair is always worth exactly 1.
+fn refund_for(
+    env: &Env,
+    market: &Market,
+    market_id: u64,
+    user: &Address,
+    position: &Position,
+) -> Result<i128, Error> {
+    let shares = position_total(position)?;
+    if !tracks_cost_basis(market) {
+        return Ok(shares);
+    }
+    let Some(basis) = get_cost_basis(env, market_id, user) else {
+        return Ok(shares / 2);
+    };
+    let owed = get_cost_basis_total(env, market_id);
+    let held = math::sub(market.pool_balance, market.total_paid_out)?;
+    if owed <= held {
+        Ok(basis)
+    } else {
+        math::mul_div(env, basis, held, owed)
+    }
+}
 
-        // Transfer payout
-        if payout > 0 {
-            let token_address = get_token(&env);
-            let token_client = token::Client::new(&env, &token_address);
-            token_client.transfer(&env.current_contract_address(), &user, &payout);
-        }
+/// Splits a scalar market's pool between LONG (YES) and SHORT (NO) holders
+/// by where `value` lands in the range, clamped at the bounds. If one side
+/// has no holders the other side takes the whole pool.
+fn scalar_payout(
+    env: &Env,
+    market: &Market,
+    position: &Position,
+    value: i128,
+) -> Result<i128, Error> {
+    let MarketKind::Scalar(range) = &market.kind else {
+        return Ok(0);
+    };
 
-        env.events()
-            .publish((symbol_short!("claim"), market_id, user), payout);
+    let long_pool = if market.total_no == 0 {
+        market.pool_balance
+    } else if market.total_yes == 0 {
+        0
+    } else {
+        let clamped = value.clamp(range.low, range.high);
+        let offset = math::sub(clamped, range.low)?;
+        let width = math::sub(range.high, range.low)?;
+        math::mul_div(env, market.pool_balance, offset, width)?
+    };
+    let short_pool = math::sub(market.pool_balance, long_pool)?;
 
-        Ok(payout)
+    let mut payout = 0;
+    if position.yes_shares > 0 {
+        payout = math::mul_div(env, position.yes_shares, long_pool, market.total_yes)?;
     }
+    if position.no_shares > 0 {
+        let short = math::mul_div(env, position.no_shares, short_pool, market.total_no)?;
+        payout = math::add(payout, short)?;
+    }
+    Ok(payout)
+}
 
-    // === View Functions ===
+fn get_protocol_fee_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ProtocolFeeBps)
+        .unwrap_or(0)
+}
 
-    /// Get market data by ID.
-    pub fn get_market(env: Env, market_id: u64) -> Result<Market, Error> {
-        bump_instance(&env);
-        get_market(&env, market_id)
-    }
+fn get_protocol_fees(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ProtocolFees(token.clone()))
+        .unwrap_or(0)
+}
 
-    /// Get a user's position in a market.
-    pub fn get_position(env: Env, market_id: u64, user: Address) -> Position {
+fn get_creator_fees(env: &Env, creator: &Address, token: &Address) -> i128 {
+    let key = DataKey::CreatorFees(creator.clone(), token.clone());
+    let fees = env.storage().persistent().get(&key).unwrap_or(0);
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
+    }
+    fees
+}
+
+fn save_creator_fees(env: &Env, creator: &Address, token: &Address, fees: i128) {
+    let key = DataKey::CreatorFees(creator.clone(), token.clone());
+    env.storage().persistent().set(&key, &fees);
+    bump_persistent(env, &key);
+}
+
+fn get_liabilities(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Liabilities(token.clone()))
+        .unwrap_or(0)
+}
+
+/// Adjusts what the contract owes in `token`, registering the token with
+/// `get_solvency` the first time it is seen. The contract owes what its
+/// markets hold and have not paid out, pending proposal bonds, and accrued
+/// protocol and creator fees; each is booked here as it changes.
+fn add_liabilities(env: &Env, token: &Address, delta: i128) -> Result<(), Error> {
+    let key = DataKey::Liabilities(token.clone());
+    if !env.storage().instance().has(&key) {
+        let mut tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiabilityTokens)
+            .unwrap_or(Vec::new(env));
+        tokens.push_back(token.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::LiabilityTokens, &tokens);
+    }
+    let total = math::add(get_liabilities(env, token), delta)?;
+    env.storage().instance().set(&key, &total);
+    Ok(())
+}
+
+/// Pulls `amount` of `token` into the contract.
+fn receive(env: &Env, token: &Address, from: &Address, amount: i128) {
+    token::Client::new(env, token).transfer(from, &env.current_contract_address(), &amount);
+}
+
+/// Pays `amount` of `token` out of the contract.
+fn pay(env: &Env, token: &Address, to: &Address, amount: i128) {
+    token::Client::new(env, token).transfer(&env.current_contract_address(), to, &amount);
+}
+
+fn is_token_allowed(env: &Env, token: &Address) -> bool {
+    let key = DataKey::AllowedToken(token.clone());
+    let allowed = env.storage().persistent().get(&key).unwrap_or(false);
+    if allowed {
+        bump_persistent(env, &key);
+    }
+    allowed
+}
+
+fn set_token_allowed(env: &Env, token: &Address, allowed: bool) {
+    let key = DataKey::AllowedToken(token.clone());
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+        bump_persistent(env, &key);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+fn index_len(env: &Env, index: &MarketIndex) -> u32 {
+    let key = DataKey::IndexLen(index.clone());
+    let len = env.storage().persistent().get(&key).unwrap_or(0);
+    if env.storage().persistent().has(&key) {
+        bump_persistent(env, &key);
+    }
+    len
+}
+
+fn set_index_entry(env: &Env, index: &MarketIndex, slot: u32, market_id: u64) {
+    let key = DataKey::IndexEntry(index.clone(), slot);
+    env.storage().persistent().set(&key, &market_id);
+    bump_persistent(env, &key);
+}
+
+/// Appends `market_id` to `index` and returns its slot.
+fn index_push(env: &Env, index: &MarketIndex, market_id: u64) -> u32 {
+    let slot = index_len(env, index);
+    set_index_entry(env, index, slot, market_id);
+    let key = DataKey::IndexLen(index.clone());
+    env.storage().persistent().set(&key, &(slot + 1));
+    bump_persistent(env, &key);
+    slot
+}
+
+/// Market IDs in slots `start..start + limit` of `index`, with `limit`
+/// capped at `MAX_PAGE_SIZE`.
+fn index_page(env: &Env, index: &MarketIndex, start: u32, limit: u32) -> Vec<u64> {
+    let end = index_len(env, index).min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+    let mut ids = Vec::new(env);
+    for slot in start..end {
+        let key = DataKey::IndexEntry(index.clone(), slot);
+        if let Some(market_id) = env.storage().persistent().get(&key) {
+            bump_persistent(env, &key);
+            ids.push_back(market_id);
+        }
+    }
+    ids
+}
+
+/// Adds a new market to the creator and unsettled indexes.
+fn index_market(env: &Env, market_id: u64, creator: &Address) {
+    index_push(env, &MarketIndex::Creator(creator.clone()), market_id);
+    let slot = index_push(env, &MarketIndex::Unsettled, market_id);
+    let key = DataKey::UnsettledSlot(market_id);
+    env.storage().persistent().set(&key, &slot);
+    bump_persistent(env, &key);
+}
+
+/// Records a market's final status and moves it to the settled index.
+fn settle(env: &Env, market_id: u64, market: &mut Market, status: MarketStatus) {
+    market.status = status;
+    market.settled_at = clock(env);
+    save_market(env, market_id, market);
+    mark_settled(env, market_id);
+}
+
+/// Moves a market from the unsettled index to the settled one. The last
+/// unsettled market takes over the vacated slot.
+fn mark_settled(env: &Env, market_id: u64) {
+    let unsettled = MarketIndex::Unsettled;
+    let slot_key = DataKey::UnsettledSlot(market_id);
+    let Some(slot) = env.storage().persistent().get::<_, u32>(&slot_key) else {
+        return;
+    };
+
+    let last = index_len(env, &unsettled) - 1;
+    if slot != last {
+        let moved: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::IndexEntry(unsettled.clone(), last))
+            .unwrap_or(market_id);
+        set_index_entry(env, &unsettled, slot, moved);
+        let moved_key = DataKey::UnsettledSlot(moved);
+        env.storage().persistent().set(&moved_key, &slot);
+        bump_persistent(env, &moved_key);
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKey::IndexEntry(unsettled.clone(), last));
+    let len_key = DataKey::IndexLen(unsettled);
+    env.storage().persistent().set(&len_key, &last);
+    bump_persistent(env, &len_key);
+    env.storage().persistent().remove(&slot_key);
+
+    index_push(env, &MarketIndex::Settled, market_id);
+}
+
+fn listing(env: &Env, market_id: u64, market: Market) -> Result<MarketListing, Error> {
+    Ok(MarketListing {
+        market_id,
+        odds: odds_bps(env, &market, market_id)?,
+        market,
+    })
+}
+
+/// Loads the markets behind `ids`, keeping only those `keep` accepts.
+fn load_markets(
+    env: &Env,
+    ids: Vec<u64>,
+    keep: impl Fn(&Market) -> bool,
+) -> Result<Vec<MarketListing>, Error> {
+    let mut markets = Vec::new(env);
+    for market_id in ids.iter() {
+        let market = get_market(env, market_id)?;
+        if keep(&market) {
+            markets.push_back(listing(env, market_id, market)?);
+        }
+    }
+    Ok(markets)
+}
+
+/// Rewrites market `market_id` from the layout of schema `version` to that of
+/// `version + 1`.
+fn migrate_market(env: &Env, version: u32, market_id: u64) -> Result<(), Error> {
+    if version == 1 {
+        // Version 2 added the listing indexes; backfill them
+        let market = get_market_v2(env, market_id)?;
+        index_market(env, market_id, &market.creator);
+        if !matches!(
+            market.status,
+            MarketStatus::Open | MarketStatus::Proposed | MarketStatus::Disputed
+        ) {
+            mark_settled(env, market_id);
+        }
+    } else if version == 2 {
+        // Version 3 added the claim window and claim accounting. Claims made
+        // before the upgrade were never totalled, so markets already settled
+        // keep claims open forever and can never be swept.
+        let old = get_market_v2(env, market_id)?;
+        let claim_window = match old.status {
+            MarketStatus::Open | MarketStatus::Proposed | MarketStatus::Disputed => {
+                get_claim_window(env)
+            }
+            _ => 0,
+        };
+        let market = MarketV3 {
+            creator: old.creator,
+            resolver: old.resolver,
+            question: old.question,
+            deadline: old.deadline,
+            kind: old.kind,
+            token: old.token,
+            creator_fee_bps: old.creator_fee_bps,
+            status: old.status,
+            total_yes: old.total_yes,
+            total_no: old.total_no,
+            outcome_totals: old.outcome_totals,
+            pool_balance: old.pool_balance,
+            claim_window,
+            settled_at: 0,
+            claimed_total: 0,
+        };
+        let key = DataKey::Market(market_id);
+        env.storage().persistent().set(&key, &market);
+        bump_persistent(env, &key);
+    } else if version == 3 {
+        // Version 4 added the trading cutoff and payout accounting, and left
+        // the resolver unset on markets meant for the admin. Under
+        // version 3 only claims and sweeps left the pool, both booked in
+        // `claimed_total`; which shares they redeemed was never recorded.
+        // Trading on existing markets runs until the deadline, as before.
+        let old = get_market_v3(env, market_id)?;
+        // Markets left to the default resolver stored the admin of the day;
+        // hand them back to whoever holds the admin key
+        let resolver = if old.resolver == get_admin(env)? {
+            None
+        } else {
+            Some(old.resolver)
+        };
+        let market = Market {
+            creator: old.creator,
+            resolver,
+            question: old.question,
+            deadline: old.deadline,
+            trading_closes_at: old.deadline,
+            kind: old.kind,
+            token: old.token,
+            creator_fee_bps: old.creator_fee_bps,
+            status: old.status,
+            total_yes: old.total_yes,
+            total_no: old.total_no,
+            outcome_totals: old.outcome_totals,
+            pool_balance: old.pool_balance,
+            claim_window: old.claim_window,
+            settled_at: old.settled_at,
+            claimed_total: old.claimed_total,
+            total_paid_out: old.claimed_total,
+            claimed_winning_shares: 0,
+        };
+        save_market(env, market_id, &market);
+        bootstrap_liabilities(env, &market.token)?;
+    }
+    Ok(())
+}
+
+/// Liabilities were never booked before schema version 4; start a token's
+/// from what the contract holds the first time migration sees it. Creator
+/// fees can't be totalled without walking every creator, so the balance is
+/// taken as owed in full, hiding any shortfall from before the upgrade.
+fn bootstrap_liabilities(env: &Env, token_address: &Address) -> Result<(), Error> {
+    if env
+        .storage()
+        .instance()
+        .has(&DataKey::Liabilities(token_address.clone()))
+    {
+        return Ok(());
+    }
+    let balance = token::Client::new(env, token_address).balance(&env.current_contract_address());
+    add_liabilities(env, token_address, balance)
+}
+
+fn get_next_market_id(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::NextMarketId)
+        .unwrap_or(0)
+}
+
+#[contract]
+pub struct PredictionMarket;
+
+#[contractimpl]
+impl PredictionMarket {
+    /// Initialize the contract with admin address and the default collateral
+    /// token (the XLM SAC), which is added to the allowlist. Can only be
+    /// called once.
+    pub fn initialize(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage().instance().set(&DataKey::NextMarketId, &0u64);
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        set_token_allowed(&env, &token, true);
+        bump_instance(&env);
+        Ok(())
+    }
+
+    /// Create a new prediction market. Returns the market ID.
+    /// LMSR markets pull the `b * ln(2)` subsidy from the creator up front.
+    /// Attaching an oracle lets anyone settle the market with
+    /// `resolve_from_oracle` after the deadline. Without a `resolver` the
+    /// market is resolved by whoever is admin at the time. `creator_fee_bps`
+    /// is taken from every purchase and accrues to the creator. `token` must
+    /// be on the allowlist and defaults to the token set at initialization.
+    /// Trading stops at `trading_closes_at`, which defaults to the deadline
+    /// and may not fall after it.
+    pub fn create_market(
+        env: Env,
+        creator: Address,
+        question: String,
+        deadline: u64,
+        kind: MarketKind,
+        oracle: Option<OracleSpec>,
+        resolver: Option<Address>,
+        creator_fee_bps: u32,
+        token: Option<Address>,
+        trading_closes_at: Option<u64>,
+    ) -> Result<u64, Error> {
+        creator.require_auth();
+        bump_instance(&env);
+        check_migrated(&env)?;
+
+        let permissionless = env
+            .storage()
+            .instance()
+            .get(&DataKey::PermissionlessCreation)
+            .unwrap_or(true);
+        if !permissionless && !has_role(&env, &Role::MarketCreator, &creator)? {
+            return Err(Error::Unauthorized);
+        }
+
+        let now = env.ledger().timestamp();
+        if deadline <= now {
+            return Err(Error::InvalidDeadline);
+        }
+        let trading_closes_at = trading_closes_at.unwrap_or(deadline);
+        if trading_closes_at <= now || trading_closes_at > deadline {
+            return Err(Error::InvalidDeadline);
+        }
+
+        if creator_fee_bps > MAX_FEE_BPS {
+            return Err(Error::InvalidFee);
+        }
+
+        let token = match token {
+            Some(token) => token,
+            None => get_token(&env)?,
+        };
+        if !is_token_allowed(&env, &token) {
+            return Err(Error::TokenNotAllowed);
+        }
+
+        let mut outcome_totals = Vec::new(&env);
+        let subsidy = match &kind {
+            MarketKind::Parimutuel | MarketKind::Cpmm => 0,
+            MarketKind::Scalar(range) => {
+                // The width must fit in an i128 for payouts to be computed
+                if range.low >= range.high || range.high.checked_sub(range.low).is_none() {
+                    return Err(Error::InvalidRange);
+                }
+                0
+            }
+            MarketKind::Lmsr(b) => {
+                if *b <= 0 {
+                    return Err(Error::InvalidLiquidity);
+                }
+                lmsr::subsidy(*b)?
+            }
+            MarketKind::Categorical(labels) => {
+                if oracle.is_some() {
+                    return Err(Error::InvalidMarketKind);
+                }
+                if labels.len() < MIN_OUTCOMES || labels.len() > MAX_OUTCOMES {
+                    return Err(Error::InvalidOutcomeCount);
+                }
+                for _ in 0..labels.len() {
+                    outcome_totals.push_back(0);
+                }
+                0
+            }
+        };
+        if subsidy > 0 {
+            receive(&env, &token, &creator, subsidy);
+            add_liabilities(&env, &token, subsidy)?;
+        }
+
+        let market_id = get_next_market_id(&env);
+
+        let market = Market {
+            creator: creator.clone(),
+            resolver,
+            question,
+            deadline,
+            trading_closes_at,
+            kind,
+            token,
+            creator_fee_bps,
+            status: MarketStatus::Open,
+            total_yes: 0,
+            total_no: 0,
+            outcome_totals,
+            pool_balance: subsidy,
+            claim_window: get_claim_window(&env),
+            settled_at: 0,
+            claimed_total: 0,
+            total_paid_out: 0,
+            claimed_winning_shares: 0,
+        };
+
+        save_market(&env, market_id, &market);
+        index_market(&env, market_id, &creator);
+        if let Some(spec) = oracle {
+            let key = DataKey::Oracle(market_id);
+            env.storage().persistent().set(&key, &spec);
+            bump_persistent(&env, &key);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::NextMarketId, &(market_id + 1));
+
+        events::market_created(&env, market_id, &market);
+
+        Ok(market_id)
+    }
+
+    /// Buy YES or NO shares for a market, or `Side::Outcome(i)` shares for a
+    /// categorical one. Amount is in the market token's smallest unit
+    /// (stroops for XLM, 1 XLM = 10_000_000), and the protocol and creator
+    /// fees are deducted from it before pricing. Parimutuel and categorical
+    /// markets issue one share per stroop; LMSR and CPMM markets price the
+    /// shares off the cost function or the pool reserves.
+    pub fn buy_shares(
+        env: Env,
+        buyer: Address,
+        market_id: u64,
+        side: Side,
+        amount: i128,
+    ) -> Result<(), Error> {
+        buyer.require_auth();
+        bump_instance(&env);
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        check_side(&market, &side)?;
+
+        if market.status != MarketStatus::Open {
+            return Err(Error::MarketClosed);
+        }
+
+        let now = env.ledger().timestamp();
+        if now >= market.trading_closes_at {
+            return Err(Error::MarketClosed);
+        }
+
+        // Transfer collateral from buyer to contract
+        receive(&env, &market.token, &buyer, amount);
+
+        // Take fees off the top; only the net stake enters the pool
+        let (protocol_fee, creator_fee) = buy_fees(&env, &market, amount)?;
+        let net = math::sub(math::sub(amount, protocol_fee)?, creator_fee)?;
+        if protocol_fee > 0 {
+            let accrued = get_protocol_fees(&env, &market.token);
+            env.storage().instance().set(
+                &DataKey::ProtocolFees(market.token.clone()),
+                &math::add(accrued, protocol_fee)?,
+            );
+            add_liabilities(&env, &market.token, protocol_fee)?;
+        }
+        if creator_fee > 0 {
+            let accrued = math::add(
+                get_creator_fees(&env, &market.creator, &market.token),
+                creator_fee,
+            )?;
+            save_creator_fees(&env, &market.creator, &market.token, accrued);
+            add_liabilities(&env, &market.token, creator_fee)?;
+        }
+
+        let (shares, pool) = shares_for_buy(&env, &market, market_id, &side, net)?;
+        if let Some(pool) = pool {
+            save_pool(&env, market_id, &pool);
+        }
+
+        // Update market totals and user position
+        let mut position = get_position(&env, market_id, &buyer);
+        credit_shares(&mut market, &mut position, &side, shares)?;
+        add_to_pool(&env, &mut market, net)?;
+        add_cost_basis(&env, &market, market_id, &buyer, net)?;
+
+        save_market(&env, market_id, &market);
+        save_position(&env, market_id, &buyer, &position);
+
+        events::trade(
+            &env,
+            symbol_short!("buy"),
+            market_id,
+            &buyer,
+            &side,
+            amount,
+            shares,
+            &market,
+        );
+
+        Ok(())
+    }
+
+    /// Sell shares back before trading closes. Parimutuel shares are refunded
+    /// 1:1 from the pool, LMSR shares at the cost-function refund and CPMM
+    /// shares at the pool's quote. Reverts with `SlippageExceeded` if the
+    /// market would pay less than `min_out`. Returns the amount paid out.
+    pub fn sell_shares(
+        env: Env,
+        seller: Address,
+        market_id: u64,
+        side: Side,
+        shares: i128,
+        min_out: i128,
+    ) -> Result<i128, Error> {
+        seller.require_auth();
+        bump_instance(&env);
+
+        if shares <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        check_side(&market, &side)?;
+
+        if market.status != MarketStatus::Open {
+            return Err(Error::MarketClosed);
+        }
+
+        let now = env.ledger().timestamp();
+        if now >= market.trading_closes_at {
+            return Err(Error::MarketClosed);
+        }
+
+        let mut position = get_position(&env, market_id, &seller);
+        if shares > position_shares(&position, &side) {
+            return Err(Error::InsufficientShares);
+        }
+
+        let amount_out = match &market.kind {
+            // Stake and shares leave together, so the remaining holders'
+            // pro-rata claims on the pool are unchanged
+            MarketKind::Parimutuel | MarketKind::Categorical(_) | MarketKind::Scalar(_) => shares,
+            MarketKind::Lmsr(b) => {
+                let (q_self, q_other) = binary_totals(&market, &side);
+                lmsr::refund_for_shares(*b, q_self, q_other, shares)?
+            }
+            MarketKind::Cpmm => {
+                let mut pool = get_pool(&env, market_id);
+                let amount_out = cpmm::sell(&mut pool, &side, shares)?;
+                save_pool(&env, market_id, &pool);
+                amount_out
+            }
+        };
+
+        if amount_out < min_out {
+            return Err(Error::SlippageExceeded);
+        }
+
+        check_pool_covers(&market, amount_out)?;
+        credit_shares(&mut market, &mut position, &side, -shares)?;
+        add_to_pool(&env, &mut market, -amount_out)?;
+        add_cost_basis(&env, &market, market_id, &seller, -amount_out)?;
+
+        save_market(&env, market_id, &market);
+        save_position(&env, market_id, &seller, &position);
+
+        if amount_out > 0 {
+            pay(&env, &market.token, &seller, amount_out);
+        }
+
+        events::trade(
+            &env,
+            symbol_short!("sell"),
+            market_id,
+            &seller,
+            &side,
+            amount_out,
+            shares,
+            &market,
+        );
+
+        Ok(amount_out)
+    }
+
+    /// Deposit collateral into a CPMM pool and receive LP shares. Once the pool
+    /// is seeded, deposits keep the current price and any surplus outcome
+    /// shares are credited to the provider's position. Returns LP shares minted.
+    pub fn add_liquidity(
+        env: Env,
+        provider: Address,
+        market_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        provider.require_auth();
+        bump_instance(&env);
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+
+        if market.kind != MarketKind::Cpmm {
+            return Err(Error::InvalidMarketKind);
+        }
+
+        if market.status != MarketStatus::Open {
+            return Err(Error::MarketClosed);
+        }
+
+        let now = env.ledger().timestamp();
+        if now >= market.trading_closes_at {
+            return Err(Error::MarketClosed);
+        }
+
+        receive(&env, &market.token, &provider, amount);
+
+        let mut pool = get_pool(&env, market_id);
+        let (yes_added, no_added, minted) = if pool.lp_supply == 0 {
+            (amount, amount, amount)
+        } else {
+            let max_reserve = pool.yes_reserve.max(pool.no_reserve);
+            (
+                math::mul_div(&env, amount, pool.yes_reserve, max_reserve)?,
+                math::mul_div(&env, amount, pool.no_reserve, max_reserve)?,
+                math::mul_div(&env, amount, pool.lp_supply, max_reserve)?,
+            )
+        };
+        pool.yes_reserve = math::add(pool.yes_reserve, yes_added)?;
+        pool.no_reserve = math::add(pool.no_reserve, no_added)?;
+        pool.lp_supply = math::add(pool.lp_supply, minted)?;
+
+        // Sets not absorbed by the pool go back to the provider as shares
+        let yes_back = math::sub(amount, yes_added)?;
+        let no_back = math::sub(amount, no_added)?;
+        if yes_back > 0 || no_back > 0 {
+            let mut position = get_position(&env, market_id, &provider);
+            credit_shares(&mut market, &mut position, &Side::Yes, yes_back)?;
+            credit_shares(&mut market, &mut position, &Side::No, no_back)?;
+            save_position(&env, market_id, &provider, &position);
+        }
+        add_to_pool(&env, &mut market, amount)?;
+        add_cost_basis(&env, &market, market_id, &provider, amount)?;
+
+        save_pool(&env, market_id, &pool);
+        save_market(&env, market_id, &market);
+        let balance = get_lp_shares(&env, market_id, &provider);
+        save_lp_shares(&env, market_id, &provider, math::add(balance, minted)?);
+
+        events::liquidity_changed(
+            &env,
+            symbol_short!("add_liq"),
+            market_id,
+            &provider,
+            amount,
+            minted,
+            &pool,
+            &market,
+        );
+
+        Ok(minted)
+    }
+
+    /// Burn LP shares for a proportional cut of the CPMM reserves. While the
+    /// market is open, matched YES/NO pairs are paid out as collateral and the
+    /// unmatched remainder is credited to the provider's position. Once
+    /// resolved, the winning reserves are paid out directly.
+    /// Returns the collateral paid out.
+    pub fn remove_liquidity(
+        env: Env,
+        provider: Address,
+        market_id: u64,
+        lp_shares: i128,
+    ) -> Result<i128, Error> {
+        provider.require_auth();
+        bump_instance(&env);
+
+        if lp_shares <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut market = get_market(&env, market_id)?;
+        // Refunds from invalid markets stay open while paused
+        if market.status != MarketStatus::Invalid {
+            check_not_paused(&env, market_id)?;
+        }
+
+        if market.kind != MarketKind::Cpmm {
+            return Err(Error::InvalidMarketKind);
+        }
+        if claim_window_closed(&env, &market) {
+            return Err(Error::ClaimWindowClosed);
+        }
+
+        let balance = get_lp_shares(&env, market_id, &provider);
+        if lp_shares > balance {
+            return Err(Error::InsufficientShares);
+        }
+
+        let mut pool = get_pool(&env, market_id);
+        let yes_out = math::mul_div(&env, pool.yes_reserve, lp_shares, pool.lp_supply)?;
+        let no_out = math::mul_div(&env, pool.no_reserve, lp_shares, pool.lp_supply)?;
+        pool.yes_reserve = math::sub(pool.yes_reserve, yes_out)?;
+        pool.no_reserve = math::sub(pool.no_reserve, no_out)?;
+        pool.lp_supply = math::sub(pool.lp_supply, lp_shares)?;
+
+        let amount_out = match &market.status {
+            MarketStatus::Open | MarketStatus::Proposed | MarketStatus::Disputed => {
+                let sets = yes_out.min(no_out);
+                if yes_out > sets || no_out > sets {
+                    let mut position = get_position(&env, market_id, &provider);
+                    let yes_back = math::sub(yes_out, sets)?;
+                    let no_back = math::sub(no_out, sets)?;
+                    credit_shares(&mut market, &mut position, &Side::Yes, yes_back)?;
+                    credit_shares(&mut market, &mut position, &Side::No, no_back)?;
+                    save_position(&env, market_id, &provider, &position);
+                }
+                add_cost_basis(&env, &market, market_id, &provider, -sets)?;
+                sets
+            }
+            MarketStatus::Resolved(Side::Yes) => yes_out,
+            MarketStatus::Resolved(Side::No) => no_out,
+            MarketStatus::Invalid => {
+                // Deposits with a cost basis are refunded by `claim_winnings`
+                if get_cost_basis(&env, market_id, &provider).is_some() {
+                    return Err(Error::MarketClosed);
+                }
+                math::add(yes_out, no_out)? / 2
+            }
+            MarketStatus::Resolved(Side::Outcome(_)) | MarketStatus::ResolvedScalar(_) => {
+                return Err(Error::InvalidMarketKind)
+            }
+        };
+        // Once settled the pool is only paid out of, never shrunk
+        if is_settled(&market) {
+            pay_from_pool(&env, &mut market, amount_out)?;
+        } else {
+            check_pool_covers(&market, amount_out)?;
+            add_to_pool(&env, &mut market, -amount_out)?;
+        }
+
+        save_pool(&env, market_id, &pool);
+        save_market(&env, market_id, &market);
+        save_lp_shares(&env, market_id, &provider, math::sub(balance, lp_shares)?);
+
+        if amount_out > 0 {
+            pay(&env, &market.token, &provider, amount_out);
+        }
+
+        events::liquidity_changed(
+            &env,
+            symbol_short!("rem_liq"),
+            market_id,
+            &provider,
+            amount_out,
+            lp_shares,
+            &pool,
+            &market,
+        );
+
+        Ok(amount_out)
+    }
+
+    /// Resolve a market. Only the market's resolver or a holder of the
+    /// `Resolver` role can call. Deadline must have passed. Also rules on
+    /// disputed proposals: whichever of the proposer and disputer was wrong
+    /// forfeits their bond to the other.
+    pub fn resolve_market(
+        env: Env,
+        caller: Address,
+        market_id: u64,
+        outcome: Side,
+    ) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        require_resolver(&env, &caller, &market)?;
+        check_side(&market, &outcome)?;
+        if let MarketKind::Scalar(_) = market.kind {
+            return Err(Error::InvalidMarketKind);
+        }
+
+        if market.status != MarketStatus::Disputed {
+            check_unresolved(&market)?;
+        }
+
+        let now = env.ledger().timestamp();
+        if now < market.deadline {
+            return Err(Error::DeadlineNotReached);
+        }
+
+        if market.status == MarketStatus::Disputed {
+            let proposal = get_proposal(&env, market_id).ok_or(Error::NotProposed)?;
+            let winner = if proposal.outcome == outcome {
+                proposal.proposer
+            } else {
+                proposal.disputer.ok_or(Error::NotProposed)?
+            };
+            let bonds = math::add(proposal.bond, proposal.bond)?;
+            pay(&env, &market.token, &winner, bonds);
+            add_liabilities(&env, &market.token, -bonds)?;
+        }
+
+        settle(&env, market_id, &mut market, MarketStatus::Resolved(outcome));
+
+        events::market_settled(&env, market_id, &market);
+
+        Ok(())
+    }
+
+    /// Resolve a scalar market with the observed value. Only the market's
+    /// resolver or a holder of the `Resolver` role can call. Deadline must
+    /// have passed.
+    pub fn resolve_scalar(
+        env: Env,
+        caller: Address,
+        market_id: u64,
+        value: i128,
+    ) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        require_resolver(&env, &caller, &market)?;
+        if !matches!(market.kind, MarketKind::Scalar(_)) {
+            return Err(Error::InvalidMarketKind);
+        }
+        check_unresolved(&market)?;
+
+        let now = env.ledger().timestamp();
+        if now < market.deadline {
+            return Err(Error::DeadlineNotReached);
+        }
+
+        settle(&env, market_id, &mut market, MarketStatus::ResolvedScalar(value));
+
+        events::market_settled(&env, market_id, &market);
+
+        Ok(())
+    }
+
+    /// Settle a market from its oracle. Anyone can call once the deadline has
+    /// passed. The market settles on the oracle's price record at the
+    /// deadline, rounded up to the oracle's resolution; until that record
+    /// exists the call fails with `OraclePriceUnavailable`.
+    pub fn resolve_from_oracle(env: Env, market_id: u64) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        let spec = get_oracle(&env, market_id).ok_or(Error::NoOracle)?;
+        check_unresolved(&market)?;
+
+        let now = env.ledger().timestamp();
+        if now < market.deadline {
+            return Err(Error::DeadlineNotReached);
+        }
+
+        // Settle on the first record at or after the deadline rather than
+        // the latest one, so the caller can't pick the price
+        let oracle = OracleClient::new(&env, &spec.oracle);
+        let resolution = oracle.resolution() as u64;
+        if resolution == 0 {
+            return Err(Error::OraclePriceUnavailable);
+        }
+        let at = market
+            .deadline
+            .div_ceil(resolution)
+            .checked_mul(resolution)
+            .ok_or(Error::Overflow)?;
+        let price = oracle
+            .price(&Asset::Other(spec.asset.clone()), &at)
+            .ok_or(Error::OraclePriceUnavailable)?;
+        if price.timestamp != at {
+            return Err(Error::OraclePriceStale);
+        }
+
+        if let MarketKind::Scalar(_) = market.kind {
+            settle(&env, market_id, &mut market, MarketStatus::ResolvedScalar(price.price));
+            events::market_settled(&env, market_id, &market);
+            return Ok(());
+        }
+
+        let holds = match spec.comparison {
+            Comparison::Gt => price.price > spec.threshold,
+            Comparison::Gte => price.price >= spec.threshold,
+            Comparison::Lt => price.price < spec.threshold,
+            Comparison::Lte => price.price <= spec.threshold,
+        };
+        let outcome = if holds { Side::Yes } else { Side::No };
+
+        settle(&env, market_id, &mut market, MarketStatus::Resolved(outcome));
+
+        events::market_settled(&env, market_id, &market);
+
+        Ok(())
+    }
+
+    /// Propose an outcome for a market whose deadline has passed, posting the
+    /// proposal bond. If nobody disputes it within the liveness window it can
+    /// be finalized by anyone.
+    pub fn propose_outcome(
+        env: Env,
+        proposer: Address,
+        market_id: u64,
+        outcome: Side,
+    ) -> Result<(), Error> {
+        proposer.require_auth();
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        check_side(&market, &outcome)?;
+        if let MarketKind::Scalar(_) = market.kind {
+            return Err(Error::InvalidMarketKind);
+        }
+        check_unresolved(&market)?;
+
+        let now = env.ledger().timestamp();
+        if now < market.deadline {
+            return Err(Error::DeadlineNotReached);
+        }
+
+        let bond: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalBond)
+            .unwrap_or(DEFAULT_PROPOSAL_BOND);
+        let liveness: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Liveness)
+            .unwrap_or(DEFAULT_LIVENESS);
+
+        receive(&env, &market.token, &proposer, bond);
+        add_liabilities(&env, &market.token, bond)?;
+
+        let proposal = Proposal {
+            proposer: proposer.clone(),
+            outcome: outcome.clone(),
+            bond,
+            expires_at: clock(&env).checked_add(liveness).ok_or(Error::Overflow)?,
+            disputer: None,
+        };
+        save_proposal(&env, market_id, &proposal);
+
+        market.status = MarketStatus::Proposed;
+        save_market(&env, market_id, &market);
+
+        events::outcome_proposed(&env, market_id, &proposal);
+
+        Ok(())
+    }
+
+    /// Dispute a pending proposal by posting a matching bond. The market is
+    /// escalated to its resolver, who settles it with `resolve_market`.
+    pub fn dispute(env: Env, disputer: Address, market_id: u64) -> Result<(), Error> {
+        disputer.require_auth();
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        if market.status != MarketStatus::Proposed {
+            return Err(Error::NotProposed);
+        }
+
+        let mut proposal = get_proposal(&env, market_id).ok_or(Error::NotProposed)?;
+        if clock(&env) >= proposal.expires_at {
+            return Err(Error::DisputeWindowClosed);
+        }
+
+        receive(&env, &market.token, &disputer, proposal.bond);
+        add_liabilities(&env, &market.token, proposal.bond)?;
+
+        proposal.disputer = Some(disputer.clone());
+        save_proposal(&env, market_id, &proposal);
+
+        market.status = MarketStatus::Disputed;
+        save_market(&env, market_id, &market);
+
+        events::proposal_disputed(&env, market_id, &disputer, proposal.bond);
+
+        Ok(())
+    }
+
+    /// Finalize an undisputed proposal once its liveness window has passed,
+    /// returning the proposer's bond. Anyone can call.
+    pub fn finalize_proposal(env: Env, market_id: u64) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        if market.status != MarketStatus::Proposed {
+            return Err(Error::NotProposed);
+        }
+
+        let proposal = get_proposal(&env, market_id).ok_or(Error::NotProposed)?;
+        if clock(&env) < proposal.expires_at {
+            return Err(Error::DisputeWindowOpen);
+        }
+
+        settle(&env, market_id, &mut market, MarketStatus::Resolved(proposal.outcome.clone()));
+
+        pay(&env, &market.token, &proposal.proposer, proposal.bond);
+        add_liabilities(&env, &market.token, -proposal.bond)?;
+
+        events::market_settled(&env, market_id, &market);
+
+        Ok(())
+    }
+
+    /// Replace a market's resolver, e.g. one that has gone unresponsive.
+    /// Only admin can call.
+    pub fn set_resolver(env: Env, market_id: u64, resolver: Address) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        market.resolver = Some(resolver.clone());
+        save_market(&env, market_id, &market);
+
+        events::resolver_changed(&env, market_id, &resolver);
+
+        Ok(())
+    }
+
+    /// Replace the contract's code with the already uploaded WASM identified
+    /// by `new_wasm_hash`. Storage is kept as is; if the new build changes
+    /// the storage layout, call `migrate` right after. Only admin can call.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        events::upgraded(&env, &new_wasm_hash);
+
+        Ok(())
+    }
+
+    /// Bring stored entries up to this build's schema version, rewriting
+    /// `Market` entries written under an older layout. Each call migrates at
+    /// most `limit` markets and picks up where the previous call stopped; the
+    /// stored schema version only advances once a step has covered every
+    /// market. Until the last step is done, calls that read or create markets
+    /// fail with `MigrationPending`. Returns the schema version reached. Only
+    /// admin can call.
+    pub fn migrate(env: Env, limit: u32) -> Result<u32, Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        let from = get_schema_version(&env);
+        if from >= SCHEMA_VERSION {
+            return Err(Error::AlreadyMigrated);
+        }
+        if limit == 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Steps run one layout version at a time, each rewriting every market
+        // from `version` to `version + 1` before the next step starts
+        let market_count = get_next_market_id(&env);
+        let mut version = from;
+        let mut cursor: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MigrationCursor)
+            .unwrap_or(0);
+        let mut budget = limit as u64;
+        while version < SCHEMA_VERSION && budget > 0 {
+            let end = market_count.min(cursor + budget);
+            for market_id in cursor..end {
+                migrate_market(&env, version, market_id)?;
+            }
+            budget -= end - cursor;
+            cursor = end;
+            if cursor == market_count {
+                if version == 3 {
+                    bootstrap_liabilities(&env, &get_token(&env)?)?;
+                }
+                version += 1;
+                cursor = 0;
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &version);
+        if version == SCHEMA_VERSION {
+            env.storage().instance().remove(&DataKey::MigrationCursor);
+        } else {
+            env.storage()
+                .instance()
+                .set(&DataKey::MigrationCursor, &cursor);
+        }
+
+        if version > from {
+            events::migrated(&env, from, version);
+        }
+
+        Ok(version)
+    }
+
+    /// Pause trading, resolution, claims and sweeps on every market.
+    /// Invalidating a market and claiming its refunds still works. Dispute and
+    /// claim windows stand still until `unpause`. Requires the `Pauser` role.
+    pub fn pause(env: Env, caller: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+        bump_instance(&env);
+
+        let storage = env.storage().instance();
+        if !storage.has(&DataKey::PausedAt) {
+            storage.set(&DataKey::PausedAt, &env.ledger().timestamp());
+        }
+        storage.set(&DataKey::Paused, &true);
+        events::pause_changed(&env, None, &caller, true);
+        Ok(())
+    }
+
+    /// Lift a global pause. Markets frozen individually stay frozen.
+    /// Requires the `Pauser` role.
+    pub fn unpause(env: Env, caller: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+        bump_instance(&env);
+
+        let storage = env.storage().instance();
+        if let Some(paused_at) = storage.get::<_, u64>(&DataKey::PausedAt) {
+            let paused_total: u64 = storage.get(&DataKey::PausedTotal).unwrap_or(0);
+            let elapsed = env.ledger().timestamp().saturating_sub(paused_at);
+            storage.set(&DataKey::PausedTotal, &paused_total.saturating_add(elapsed));
+            storage.remove(&DataKey::PausedAt);
+        }
+        storage.remove(&DataKey::Paused);
+        events::pause_changed(&env, None, &caller, false);
+        Ok(())
+    }
+
+    /// Pause a single market, with the same exceptions as `pause`. Its
+    /// dispute and claim windows are extended by the time it stays frozen.
+    /// Requires the `Pauser` role.
+    pub fn freeze_market(env: Env, caller: Address, market_id: u64) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+        bump_instance(&env);
+
+        get_market(&env, market_id)?;
+        if !is_frozen(&env, market_id) {
+            let key = DataKey::FrozenAt(market_id);
+            env.storage().persistent().set(&key, &clock(&env));
+            bump_persistent(&env, &key);
+        }
+        let key = DataKey::Frozen(market_id);
+        env.storage().persistent().set(&key, &true);
+        bump_persistent(&env, &key);
+        events::pause_changed(&env, Some(market_id), &caller, true);
+        Ok(())
+    }
+
+    /// Lift a freeze on a single market. Requires the `Pauser` role.
+    pub fn unfreeze_market(env: Env, caller: Address, market_id: u64) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        let key = DataKey::FrozenAt(market_id);
+        if let Some(frozen_at) = env.storage().persistent().get::<_, u64>(&key) {
+            // Push out whichever window was running when the market froze
+            let elapsed = clock(&env).saturating_sub(frozen_at);
+            if market.status == MarketStatus::Proposed {
+                if let Some(mut proposal) = get_proposal(&env, market_id) {
+                    proposal.expires_at = proposal.expires_at.saturating_add(elapsed);
+                    save_proposal(&env, market_id, &proposal);
+                }
+            } else if is_settled(&market) {
+                // Only the part of the freeze after settlement counts
+                let elapsed = clock(&env).saturating_sub(frozen_at.max(market.settled_at));
+                market.settled_at = market.settled_at.saturating_add(elapsed);
+                save_market(&env, market_id, &market);
+            }
+            env.storage().persistent().remove(&key);
+        }
+        env.storage().persistent().remove(&DataKey::Frozen(market_id));
+        events::pause_changed(&env, Some(market_id), &caller, false);
+        Ok(())
+    }
+
+    /// Grant `role` to `account`. Only admin can call.
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        let key = DataKey::Role(role.clone(), account.clone());
+        env.storage().persistent().set(&key, &true);
+        bump_persistent(&env, &key);
+
+        events::role_changed(&env, &role, &account, true);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account`. Only admin can call.
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Role(role.clone(), account.clone()));
+
+        events::role_changed(&env, &role, &account, false);
+        Ok(())
+    }
+
+    /// Turn permissionless market creation on or off. While off, only
+    /// holders of the `MarketCreator` role can create markets. Only admin
+    /// can call.
+    pub fn set_permissionless_creation(env: Env, enabled: bool) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PermissionlessCreation, &enabled);
+        events::creation_changed(&env, enabled);
+        Ok(())
+    }
+
+    /// Nominate `new_admin` to take over the contract. The transfer only
+    /// happens once they call `accept_admin`, and replaces any earlier
+    /// nomination. Only admin can call.
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        bump_instance(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+
+        events::admin_transfer(&env, symbol_short!("adm_prop"), &admin, &new_admin);
+
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination. Only the nominated address can
+    /// call.
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let new_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(Error::NoPendingAdmin)?;
+        new_admin.require_auth();
+
+        let old_admin = get_admin(&env)?;
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        events::admin_transfer(&env, symbol_short!("admin"), &old_admin, &new_admin);
+
+        Ok(())
+    }
+
+    /// Withdraw a pending admin nomination. Only admin can call.
+    pub fn cancel_admin_transfer(env: Env) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        bump_instance(&env);
+
+        let pending: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(Error::NoPendingAdmin)?;
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        events::admin_transfer(&env, symbol_short!("adm_cncl"), &admin, &pending);
+
+        Ok(())
+    }
+
+    /// Set the protocol fee taken from every purchase, in basis points.
+    /// Requires the `FeeManager` role.
+    pub fn set_protocol_fee(env: Env, caller: Address, fee_bps: u32) -> Result<(), Error> {
+        require_role(&env, &caller, Role::FeeManager)?;
+        bump_instance(&env);
+
+        if fee_bps > MAX_FEE_BPS {
+            return Err(Error::InvalidFee);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ProtocolFeeBps, &fee_bps);
+        events::protocol_fee_changed(&env, &caller, fee_bps);
+        Ok(())
+    }
+
+    /// Withdraw all protocol fees accrued in `token` to `to`. Requires the
+    /// `FeeManager` role. Returns the amount withdrawn.
+    pub fn withdraw_protocol_fees(
+        env: Env,
+        caller: Address,
+        token: Address,
+        to: Address,
+    ) -> Result<i128, Error> {
+        require_role(&env, &caller, Role::FeeManager)?;
+        bump_instance(&env);
+
+        let amount = get_protocol_fees(&env, &token);
+        if amount == 0 {
+            return Err(Error::NothingToClaim);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ProtocolFees(token.clone()), &0i128);
+
+        pay(&env, &token, &to, amount);
+        add_liabilities(&env, &token, -amount)?;
+
+        events::fees_withdrawn(&env, &token, &to, amount);
+
+        Ok(amount)
+    }
+
+    /// Withdraw all creator fees accrued in `token` across the creator's
+    /// markets. Returns the amount withdrawn.
+    pub fn withdraw_creator_fees(
+        env: Env,
+        creator: Address,
+        token: Address,
+    ) -> Result<i128, Error> {
+        creator.require_auth();
+        bump_instance(&env);
+
+        let amount = get_creator_fees(&env, &creator, &token);
+        if amount == 0 {
+            return Err(Error::NothingToClaim);
+        }
+        save_creator_fees(&env, &creator, &token, 0);
+
+        pay(&env, &token, &creator, amount);
+        add_liabilities(&env, &token, -amount)?;
+
+        events::fees_withdrawn(&env, &token, &creator, amount);
+
+        Ok(amount)
+    }
+
+    /// Add `token` to the allowlist of collateral tokens markets can be
+    /// created with. Only admin can call.
+    pub fn allow_token(env: Env, token: Address) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        set_token_allowed(&env, &token, true);
+        events::token_allowed(&env, &token, true);
+        Ok(())
+    }
+
+    /// Remove `token` from the allowlist. Existing markets keep trading and
+    /// settling in it; only new markets are refused. Only admin can call.
+    pub fn disallow_token(env: Env, token: Address) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        set_token_allowed(&env, &token, false);
+        events::token_allowed(&env, &token, false);
+        Ok(())
+    }
+
+    /// Set the bond required to propose or dispute an outcome and the length
+    /// of the dispute window in seconds, at most 30 days. Only admin can call.
+    pub fn set_dispute_params(env: Env, bond: i128, liveness: u64) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        if bond <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if liveness == 0 || liveness > MAX_LIVENESS {
+            return Err(Error::InvalidLiveness);
+        }
+        env.storage().instance().set(&DataKey::ProposalBond, &bond);
+        env.storage().instance().set(&DataKey::Liveness, &liveness);
+        events::dispute_params_changed(&env, bond, liveness);
+        Ok(())
+    }
+
+    /// Set how long, in seconds after settlement, holders of markets created
+    /// from now on may claim; zero keeps claims open forever. Existing markets
+    /// keep their window. Only admin can call.
+    pub fn set_claim_window(env: Env, window: u64) -> Result<(), Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        env.storage().instance().set(&DataKey::ClaimWindow, &window);
+        events::claim_window_changed(&env, window);
+        Ok(())
+    }
+
+    /// Send everything a settled market still holds to `to` once its claim
+    /// window has closed: unclaimed payouts, liquidity never withdrawn and
+    /// rounding dust. Only admin can call, and not while the market is
+    /// paused. Returns the amount swept.
+    pub fn sweep_market(env: Env, market_id: u64, to: Address) -> Result<i128, Error> {
+        require_admin(&env)?;
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_not_paused(&env, market_id)?;
+        if !is_settled(&market) {
+            return Err(Error::MarketNotResolved);
+        }
+        if !claim_window_closed(&env, &market) {
+            return Err(Error::ClaimWindowOpen);
+        }
+
+        let amount = math::sub(market.pool_balance, market.total_paid_out)?;
+        if amount <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        pay_from_pool(&env, &mut market, amount)?;
+        save_market(&env, market_id, &market);
+
+        pay(&env, &market.token, &to, amount);
+
+        events::market_swept(&env, market_id, &to, amount);
+
+        Ok(amount)
+    }
+
+    /// Return what a settled LMSR market holds beyond its outstanding
+    /// shares to the creator: the unspent part of the subsidy plus the
+    /// market maker's takings. Every share still unclaimed stays covered.
+    /// An invalid market pays its creator only once every position has been
+    /// refunded. Only the creator can call. Returns the amount withdrawn.
+    pub fn withdraw_surplus(env: Env, market_id: u64) -> Result<i128, Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        market.creator.require_auth();
+        check_not_paused(&env, market_id)?;
+        if !matches!(market.kind, MarketKind::Lmsr(_)) {
+            return Err(Error::InvalidMarketKind);
+        }
+
+        // Winning shares redeem at 1 each. Refunds in an invalid market are
+        // scaled to what the pool holds, so they must all be paid (or the
+        // claim window closed) first, or a creator who voided their own
+        // market could keep what traders paid.
+        let owed = match &market.status {
+            MarketStatus::Resolved(side) => {
+                math::sub(market_shares(&market, side), market.claimed_winning_shares)?
+            }
+            MarketStatus::Invalid => {
+                let shares = math::add(market.total_yes, market.total_no)?;
+                let pending = market.claimed_winning_shares < shares
+                    || get_cost_basis_total(&env, market_id) > 0;
+                if pending && !claim_window_closed(&env, &market) {
+                    return Err(Error::RefundsPending);
+                }
+                0
+            }
+            _ => return Err(Error::MarketNotResolved),
+        };
+        let held = math::sub(market.pool_balance, market.total_paid_out)?;
+        let amount = math::sub(held, owed)?;
+        if amount <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        pay_from_pool(&env, &mut market, amount)?;
+        save_market(&env, market_id, &market);
+
+        pay(&env, &market.token, &market.creator, amount);
+
+        events::surplus_withdrawn(&env, market_id, &market.creator, amount);
+
+        Ok(amount)
+    }
+
+    /// Void a market whose question was ambiguous or whose event never
+    /// happened. Only the market's resolver or a holder of the `Resolver`
+    /// role can call, at any time before the market is resolved. Any pending
+    /// proposal bonds are returned, and `claim_winnings` refunds every
+    /// position: stakes in full, and what was paid into LMSR and CPMM
+    /// markets less what was taken back out.
+    pub fn invalidate_market(env: Env, caller: Address, market_id: u64) -> Result<(), Error> {
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        require_resolver(&env, &caller, &market)?;
+        match market.status {
+            MarketStatus::Open => {}
+            MarketStatus::Proposed | MarketStatus::Disputed => {
+                let proposal = get_proposal(&env, market_id).ok_or(Error::NotProposed)?;
+                pay(&env, &market.token, &proposal.proposer, proposal.bond);
+                add_liabilities(&env, &market.token, -proposal.bond)?;
+                if let Some(disputer) = proposal.disputer {
+                    pay(&env, &market.token, &disputer, proposal.bond);
+                    add_liabilities(&env, &market.token, -proposal.bond)?;
+                }
+            }
+            _ => return Err(Error::MarketAlreadyResolved),
+        }
+
+        settle(&env, market_id, &mut market, MarketStatus::Invalid);
+
+        events::market_settled(&env, market_id, &market);
+
+        Ok(())
+    }
+
+    /// Claim winnings from a resolved market, or a refund from an invalid one.
+    pub fn claim_winnings(env: Env, user: Address, market_id: u64) -> Result<i128, Error> {
+        user.require_auth();
+        bump_instance(&env);
+
+        let mut market = get_market(&env, market_id)?;
+        check_claims_open(&env, &market, market_id)?;
+        let mut position = get_position(&env, market_id, &user);
+
+        if position.claimed {
+            return Err(Error::AlreadyClaimed);
+        }
+
+        let payout = payout_for(&env, &market, market_id, &user, &position)?;
+
+        // Mark as claimed before transfer (checks-effects-interactions). A
+        // user who never held shares has nothing to mark, and stays out of
+        // the position index. Empty-handed claims are booked too, so an
+        // invalid market knows when every position has been settled.
+        let held = position_total(&position)? > 0;
+        if held {
+            position.claimed = true;
+            save_position(&env, market_id, &user, &position);
+        }
+        if held || payout > 0 {
+            record_claim(&env, &mut market, market_id, &user, &position, payout)?;
+            save_market(&env, market_id, &market);
+        }
+        if payout > 0 {
+            pay(&env, &market.token, &user, payout);
+        }
+
+        events::claimed(&env, market_id, &user, payout);
+
+        Ok(payout)
+    }
+
+    /// Claim from several markets at once. Payouts are summed per collateral
+    /// token and sent with one transfer each. Markets that cannot pay out are
+    /// skipped and reported in the returned results rather than aborting the
+    /// batch; an unknown market id still fails the whole call.
+    pub fn claim_many(
+        env: Env,
+        user: Address,
+        market_ids: Vec<u64>,
+    ) -> Result<Vec<ClaimResult>, Error> {
+        user.require_auth();
+        bump_instance(&env);
+
+        if market_ids.is_empty() || market_ids.len() > MAX_CLAIM_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+
+        let mut results = Vec::new(&env);
+        let mut totals: Map<Address, i128> = Map::new(&env);
+
+        for market_id in market_ids.iter() {
+            let mut market = get_market(&env, market_id)?;
+            let (status, payout) = if let Err(err) = check_claims_open(&env, &market, market_id) {
+                match err {
+                    Error::MarketNotResolved => (ClaimStatus::NotSettled, 0),
+                    Error::Paused => (ClaimStatus::Paused, 0),
+                    Error::ClaimWindowClosed => (ClaimStatus::Expired, 0),
+                    err => return Err(err),
+                }
+            } else {
+                let mut position = get_position(&env, market_id, &user);
+                let payout = payout_for(&env, &market, market_id, &user, &position)?;
+                if position.claimed {
+                    (ClaimStatus::AlreadyClaimed, 0)
+                } else if payout == 0 {
+                    (ClaimStatus::NoPayout, 0)
+                } else {
+                    position.claimed = true;
+                    save_position(&env, market_id, &user, &position);
+                    record_claim(&env, &mut market, market_id, &user, &position, payout)?;
+                    save_market(&env, market_id, &market);
+                    let total = totals.get(market.token.clone()).unwrap_or(0);
+                    totals.set(market.token.clone(), math::add(total, payout)?);
+                    events::claimed(&env, market_id, &user, payout);
+                    (ClaimStatus::Paid, payout)
+                }
+            };
+            results.push_back(ClaimResult {
+                market_id,
+                status,
+                payout,
+            });
+        }
+
+        for (token_address, total) in totals.iter() {
+            pay(&env, &token_address, &user, total);
+        }
+
+        Ok(results)
+    }
+
+    // === View Functions ===
+
+    /// Check whether `account` holds `role`, directly or as admin.
+    pub fn has_role(env: Env, role: Role, account: Address) -> Result<bool, Error> {
+        bump_instance(&env);
+        has_role(&env, &role, &account)
+    }
+
+    /// Check whether anyone may create markets.
+    pub fn is_permissionless_creation(env: Env) -> bool {
+        bump_instance(&env);
+        env.storage()
+            .instance()
+            .get(&DataKey::PermissionlessCreation)
+            .unwrap_or(true)
+    }
+
+    /// Get the storage schema version.
+    pub fn get_schema_version(env: Env) -> u32 {
+        bump_instance(&env);
+        get_schema_version(&env)
+    }
+
+    /// Get the current admin.
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        bump_instance(&env);
+        get_admin(&env)
+    }
+
+    /// Get the admin nominated by `propose_admin`, if a transfer is pending.
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        bump_instance(&env);
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Get market data by ID.
+    pub fn get_market(env: Env, market_id: u64) -> Result<Market, Error> {
+        bump_instance(&env);
+        get_market(&env, market_id)
+    }
+
+    /// Get a user's position in a market.
+    pub fn get_position(env: Env, market_id: u64, user: Address) -> Position {
         bump_instance(&env);
         get_position(&env, market_id, &user)
     }
 
+    /// Implied probability of each side in basis points: `[yes, no]` for
+    /// binary and scalar markets, one entry per outcome for categorical ones.
+    pub fn get_odds(env: Env, market_id: u64) -> Result<Vec<u32>, Error> {
+        bump_instance(&env);
+        let market = get_market(&env, market_id)?;
+        odds_bps(&env, &market, market_id)
+    }
+
+    /// Payout a buy of `amount` on `side` would collect if that side wins and
+    /// nobody else trades, after fees. For scalar markets this is the payout
+    /// with the value at the side's favourable bound.
+    pub fn quote_buy(env: Env, market_id: u64, side: Side, amount: i128) -> Result<i128, Error> {
+        bump_instance(&env);
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let market = get_market(&env, market_id)?;
+        check_side(&market, &side)?;
+        let now = env.ledger().timestamp();
+        if market.status != MarketStatus::Open || now >= market.trading_closes_at {
+            return Err(Error::MarketClosed);
+        }
+
+        let (protocol_fee, creator_fee) = buy_fees(&env, &market, amount)?;
+        let net = math::sub(math::sub(amount, protocol_fee)?, creator_fee)?;
+        let (shares, _) = shares_for_buy(&env, &market, market_id, &side, net)?;
+
+        let payout = if let MarketKind::Lmsr(_) | MarketKind::Cpmm = market.kind {
+            shares
+        } else {
+            // The winning side splits the whole pool, this stake included
+            let winning_shares = math::add(market_shares(&market, &side), shares)?;
+            let pool = math::add(market.pool_balance, net)?;
+            math::mul_div(&env, shares, pool, winning_shares)?
+        };
+        Ok(payout)
+    }
+
+    /// Amount `claim_winnings` would pay `user` right now; zero wherever the
+    /// claim would be refused (unsettled, paused, frozen or expired) and
+    /// after claiming.
+    pub fn get_claimable(env: Env, market_id: u64, user: Address) -> Result<i128, Error> {
+        bump_instance(&env);
+        let market = get_market(&env, market_id)?;
+        let position = get_position(&env, market_id, &user);
+        claimable_for(&env, &market, market_id, &user, &position)
+    }
+
+    /// What the contract owes in each token it has handled — open pools,
+    /// unclaimed payouts, bonds and unwithdrawn fees — against the balance it
+    /// actually holds. The contract is solvent while every balance covers
+    /// its liabilities.
+    pub fn get_solvency(env: Env) -> Vec<TokenSolvency> {
+        bump_instance(&env);
+        let tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiabilityTokens)
+            .unwrap_or(Vec::new(&env));
+        let contract = env.current_contract_address();
+        let mut report = Vec::new(&env);
+        for token_address in tokens.iter() {
+            report.push_back(TokenSolvency {
+                liabilities: get_liabilities(&env, &token_address),
+                balance: token::Client::new(&env, &token_address).balance(&contract),
+                token: token_address,
+            });
+        }
+        report
+    }
+
+    /// Claim window stamped on newly created markets, in seconds.
+    pub fn get_claim_window(env: Env) -> u64 {
+        bump_instance(&env);
+        get_claim_window(&env)
+    }
+
+    /// Get the oracle a market settles from, if any.
+    pub fn get_oracle(env: Env, market_id: u64) -> Option<OracleSpec> {
+        bump_instance(&env);
+        get_oracle(&env, market_id)
+    }
+
+    /// Get the outcome proposal for a market, if any.
+    pub fn get_proposal(env: Env, market_id: u64) -> Option<Proposal> {
+        bump_instance(&env);
+        get_proposal(&env, market_id)
+    }
+
+    /// Get the reserves of a CPMM market.
+    pub fn get_pool(env: Env, market_id: u64) -> Result<CpmmPool, Error> {
+        bump_instance(&env);
+        let market = get_market(&env, market_id)?;
+        if market.kind != MarketKind::Cpmm {
+            return Err(Error::InvalidMarketKind);
+        }
+        Ok(get_pool(&env, market_id))
+    }
+
+    /// Get a provider's LP shares in a CPMM market.
+    pub fn get_lp_shares(env: Env, market_id: u64, provider: Address) -> i128 {
+        bump_instance(&env);
+        get_lp_shares(&env, market_id, &provider)
+    }
+
+    /// Get the protocol fee in basis points.
+    pub fn get_protocol_fee(env: Env) -> u32 {
+        bump_instance(&env);
+        get_protocol_fee_bps(&env)
+    }
+
+    /// Get protocol fees accrued in `token` and not yet withdrawn.
+    pub fn get_protocol_fees(env: Env, token: Address) -> i128 {
+        bump_instance(&env);
+        get_protocol_fees(&env, &token)
+    }
+
+    /// Get a creator's fees accrued in `token` and not yet withdrawn.
+    pub fn get_creator_fees(env: Env, creator: Address, token: Address) -> i128 {
+        bump_instance(&env);
+        get_creator_fees(&env, &creator, &token)
+    }
+
+    /// Check whether `token` can be used as collateral for new markets.
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        bump_instance(&env);
+        is_token_allowed(&env, &token)
+    }
+
+    /// Current reading of the contract clock: ledger seconds not spent
+    /// paused. Proposal expiries and settlement times are on this clock.
+    pub fn get_clock(env: Env) -> u64 {
+        bump_instance(&env);
+        clock(&env)
+    }
+
+    /// Check whether the whole contract is paused.
+    pub fn is_paused(env: Env) -> bool {
+        bump_instance(&env);
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Check whether a market is frozen on its own.
+    pub fn is_market_frozen(env: Env, market_id: u64) -> bool {
+        bump_instance(&env);
+        is_frozen(&env, market_id)
+    }
+
+    /// List markets by ID, starting at `start`. Returns at most `limit`
+    /// markets (capped at 50).
+    pub fn list_markets(env: Env, start: u64, limit: u32) -> Result<Vec<MarketListing>, Error> {
+        bump_instance(&env);
+        let limit = limit.min(MAX_PAGE_SIZE) as u64;
+        let end = get_next_market_id(&env).min(start.saturating_add(limit));
+        let mut markets = Vec::new(&env);
+        for market_id in start..end {
+            markets.push_back(listing(&env, market_id, get_market(&env, market_id)?)?);
+        }
+        Ok(markets)
+    }
+
+    /// List markets matching `status`. `start` and `limit` page through the
+    /// underlying index: open and awaiting-resolution markets share one
+    /// index and are told apart by trading cutoff when read, so their pages
+    /// may hold fewer than `limit` markets. Settled markets come back in the
+    /// order they were settled.
+    pub fn list_markets_by_status(
+        env: Env,
+        status: StatusFilter,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<MarketListing>, Error> {
+        bump_instance(&env);
+        let now = env.ledger().timestamp();
+        match status {
+            StatusFilter::Open => {
+                let ids = index_page(&env, &MarketIndex::Unsettled, start, limit);
+                load_markets(&env, ids, |m| {
+                    m.status == MarketStatus::Open && now < m.trading_closes_at
+                })
+            }
+            StatusFilter::AwaitingResolution => {
+                let ids = index_page(&env, &MarketIndex::Unsettled, start, limit);
+                load_markets(&env, ids, |m| {
+                    m.status != MarketStatus::Open || now >= m.trading_closes_at
+                })
+            }
+            StatusFilter::Resolved => {
+                let ids = index_page(&env, &MarketIndex::Settled, start, limit);
+                load_markets(&env, ids, |_| true)
+            }
+        }
+    }
+
+    /// List markets created by `creator`, oldest first.
+    pub fn list_markets_by_creator(
+        env: Env,
+        creator: Address,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<MarketListing>, Error> {
+        bump_instance(&env);
+        let ids = index_page(&env, &MarketIndex::Creator(creator), start, limit);
+        load_markets(&env, ids, |_| true)
+    }
+
+    /// List the markets `user` holds positions in, oldest first, with each
+    /// position and the amount currently claimable from it. Returns at most
+    /// `limit` entries (capped at 50). Positions opened before schema version
+    /// 3 are not listed: they are keyed by market and user, so `migrate` has
+    /// no way to find them and backfill the index.
+    pub fn get_user_positions(
+        env: Env,
+        user: Address,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<UserPosition>, Error> {
+        bump_instance(&env);
+        let ids = index_page(&env, &MarketIndex::User(user.clone()), start, limit);
+        let mut positions = Vec::new(&env);
+        for market_id in ids.iter() {
+            let market = get_market(&env, market_id)?;
+            let position = get_position(&env, market_id, &user);
+            positions.push_back(UserPosition {
+                market_id,
+                claimable: claimable_for(&env, &market, market_id, &user, &position)?,
+                market,
+                position,
+            });
+        }
+        Ok(positions)
+    }
+
     /// Get total number of markets created.
     pub fn get_market_count(env: Env) -> u64 {
         bump_instance(&env);
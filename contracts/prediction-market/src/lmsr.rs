@@ -0,0 +1,149 @@
+//! Fixed-point math for the logarithmic market scoring rule (LMSR).
+//!
+//! The binary cost function is `C(q) = b * ln(e^(q_yes/b) + e^(q_no/b))`.
+//! Buying YES with `c` units of collateral moves the state to the `q_yes'`
+//! that satisfies `C(q') - C(q) = c`, which has the closed form
+//!
+//! `shares = c + b * ln(1 + e^((q_no - q_yes)/b) * (1 - e^(-c/b)))`
+//!
+//! Selling `s` YES shares refunds `C(q) - C(q_yes - s, q_no)`, i.e.
+//!
+//! `refund = b * (ln(1 + e^d) - ln(e^(-s/b) + e^d))` with `d = (q_no - q_yes)/b`
+//!
+//! All values below are fixed point with 18 decimals unless noted otherwise.
+//! Every exponential is evaluated on a non-positive argument so intermediate
+//! values stay bounded regardless of how lopsided the market becomes.
+
+use crate::errors::Error;
+use crate::math;
+
+pub const SCALE: i128 = 1_000_000_000_000_000_000;
+const LN2: i128 = 693_147_180_559_945_309;
+
+/// `e^x` for `x <= 0`.
+fn exp_neg(x: i128) -> i128 {
+    // Split x = -k*ln2 + r with r in (-ln2, 0] so the series converges fast.
+    let k = -x / LN2;
+    if k >= 64 {
+        return 0;
+    }
+    let r = x + k * LN2;
+
+    let mut sum = SCALE;
+    let mut term = SCALE;
+    let mut i = 1;
+    loop {
+        term = term * r / SCALE / i;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+        i += 1;
+    }
+    sum >> k
+}
+
+/// Natural logarithm of a positive fixed-point value.
+fn ln(x: i128) -> i128 {
+    // Normalize x = m * 2^k with m in [1, 2).
+    let mut m = x;
+    let mut k: i128 = 0;
+    while m >= 2 * SCALE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < SCALE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3.
+    let z = (m - SCALE) * SCALE / (m + SCALE);
+    let z2 = z * z / SCALE;
+    let mut sum = 0;
+    let mut term = z;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z2 / SCALE;
+        n += 2;
+    }
+    2 * sum + k * LN2
+}
+
+/// `ln(1 + e^t)`, evaluated without overflowing for large `t`.
+fn softplus(t: i128) -> Result<i128, Error> {
+    if t > 0 {
+        math::add(t, ln(SCALE + exp_neg(-t)))
+    } else {
+        Ok(ln(SCALE + exp_neg(t)))
+    }
+}
+
+/// Converts a stroop amount into a fixed-point multiple of `b`.
+fn to_fixed(amount: i128, b: i128) -> Result<i128, Error> {
+    amount
+        .checked_mul(SCALE)
+        .map(|v| v / b)
+        .ok_or(Error::Overflow)
+}
+
+/// Creator subsidy needed to back a binary LMSR market: the worst-case
+/// loss `b * ln(2)`, rounded up.
+pub fn subsidy(b: i128) -> Result<i128, Error> {
+    let loss = b.checked_mul(LN2).ok_or(Error::InvalidLiquidity)?;
+    Ok(math::add(loss, SCALE - 1)? / SCALE)
+}
+
+/// Instantaneous price of one side in basis points, `1 / (1 + e^d)` with
+/// `d = (q_other - q_self)/b`. Rounded down.
+pub fn price_bps(b: i128, q_self: i128, q_other: i128) -> Result<u32, Error> {
+    let d = to_fixed(math::sub(q_other, q_self)?, b)?;
+    let price = if d >= 0 {
+        let e = exp_neg(-d);
+        e * SCALE / (SCALE + e)
+    } else {
+        SCALE * SCALE / (SCALE + exp_neg(d))
+    };
+    Ok((price * 10_000 / SCALE) as u32)
+}
+
+/// Number of shares on one side bought by paying `cost` stroops, given the
+/// outstanding shares on that side (`q_self`) and the other side (`q_other`).
+/// Rounded down so the pool always stays covered.
+pub fn shares_for_cost(b: i128, q_self: i128, q_other: i128, cost: i128) -> Result<i128, Error> {
+    let a = to_fixed(cost, b)?;
+    let d = to_fixed(math::sub(q_other, q_self)?, b)?;
+
+    let y = SCALE - exp_neg(-a);
+    if y <= 0 {
+        return Ok(cost);
+    }
+
+    let extra = b
+        .checked_mul(softplus(math::add(d, ln(y))?)?)
+        .ok_or(Error::Overflow)?
+        / SCALE;
+    cost.checked_add(extra).ok_or(Error::Overflow)
+}
+
+/// Collateral refunded for selling `shares` of one side back to the market
+/// maker. Rounded down so the pool always stays covered.
+pub fn refund_for_shares(
+    b: i128,
+    q_self: i128,
+    q_other: i128,
+    shares: i128,
+) -> Result<i128, Error> {
+    let a = to_fixed(shares, b)?;
+    let d = to_fixed(math::sub(q_other, q_self)?, b)?;
+
+    // ln(e^-a + e^d) = max(-a, d) + ln(1 + e^-|d + a|)
+    let gap = math::add(d, a)?.checked_abs().ok_or(Error::Overflow)?;
+    let ln_after = math::add((-a).max(d), softplus(-gap)?)?;
+    let refund = b
+        .checked_mul(math::sub(softplus(d)?, ln_after)?)
+        .ok_or(Error::Overflow)?
+        / SCALE;
+    Ok(refund.max(0))
+}
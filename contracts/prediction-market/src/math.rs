@@ -0,0 +1,37 @@
+//! Checked arithmetic for collateral amounts and share counts.
+//!
+//! Every helper fails with `Error::Overflow` where plain `i128` math would
+//! trap. `mul_div` widens to 256 bits when the product does not fit, so
+//! pro-rata payouts on very large pools still settle.
+
+use soroban_sdk::{Env, I256};
+
+use crate::errors::Error;
+
+pub fn add(a: i128, b: i128) -> Result<i128, Error> {
+    a.checked_add(b).ok_or(Error::Overflow)
+}
+
+pub fn sub(a: i128, b: i128) -> Result<i128, Error> {
+    a.checked_sub(b).ok_or(Error::Overflow)
+}
+
+pub fn sum(values: impl IntoIterator<Item = i128>) -> Result<i128, Error> {
+    values.into_iter().try_fold(0, add)
+}
+
+/// `a * b / d`, rounded toward zero. Fails if `d` is zero or the quotient
+/// does not fit in an `i128`.
+pub fn mul_div(env: &Env, a: i128, b: i128, d: i128) -> Result<i128, Error> {
+    if d == 0 {
+        return Err(Error::Overflow);
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Ok(product / d);
+    }
+    I256::from_i128(env, a)
+        .mul(&I256::from_i128(env, b))
+        .div(&I256::from_i128(env, d))
+        .to_i128()
+        .ok_or(Error::Overflow)
+}
@@ -0,0 +1,33 @@
+//! Client interface for SEP-40 style price feed oracles.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+/// Asset identifier as defined by SEP-40.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// Price record returned by a SEP-40 oracle. `price` uses the oracle's own
+/// decimals.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[allow(dead_code)]
+#[contractclient(name = "OracleClient")]
+pub trait PriceFeed {
+    /// Most recent price for `asset`, if the oracle has one.
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+
+    /// Price for `asset` recorded at `timestamp`, if the oracle has one.
+    fn price(env: Env, asset: Asset, timestamp: u64) -> Option<PriceData>;
+
+    /// Seconds between price records.
+    fn resolution(env: Env) -> u32;
+}
@@ -1,9 +1,51 @@
 #![cfg(test)]
+// The original tests are kept as written rather than reshaped for clippy.
+#![allow(clippy::assign_op_pattern, clippy::bool_assert_comparison)]
 
-use crate::types::{MarketStatus, Side};
+use crate::errors::Error;
+use crate::events::{
+    AdminTransfer, ClaimWindowChanged, Claimed, CreationChanged, DisputeParamsChanged,
+    FeesWithdrawn, LiquidityChanged, MarketCreated, MarketSettled, MarketSwept, Migrated,
+    OutcomeProposed, PauseChanged, ProposalDisputed, ProtocolFeeChanged, ResolverChanged,
+    RoleChanged, TokenAllowed, Trade, EVENT_VERSION,
+};
+use crate::oracle::{Asset, PriceData};
+use crate::types::{
+    ClaimResult, ClaimStatus, Comparison, DataKey, Market, MarketKind, MarketListing, MarketStatus,
+    OracleSpec, Role, ScalarRange, Side, StatusFilter, TokenSolvency,
+};
 use crate::{PredictionMarket, PredictionMarketClient};
-use soroban_sdk::testutils::{Address as _, Ledger};
-use soroban_sdk::{token, Address, Env, String};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, vec, Address, BytesN, Env, IntoVal, String,
+    Val,
+};
+
+/// SEP-40 price feed stand-in with five-minute records, serving whatever
+/// prices were set.
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        let record = PriceData { price, timestamp };
+        env.storage().instance().set(&asset, &record);
+        env.storage().instance().set(&(asset, timestamp), &record);
+    }
+
+    pub fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().instance().get(&asset)
+    }
+
+    pub fn price(env: Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        env.storage().instance().get(&(asset, timestamp))
+    }
+
+    pub fn resolution(_env: Env) -> u32 {
+        300
+    }
+}
 
 struct TestSetup<'a> {
     env: Env,
@@ -39,8 +81,32 @@ fn setup() -> TestSetup<'static> {
 
 fn create_test_market(s: &TestSetup, deadline_offset: u64) -> u64 {
     let deadline = s.env.ledger().timestamp() + deadline_offset;
-    s.client
-        .create_market(&s.admin, &String::from_str(&s.env, "Will it rain tomorrow?"), &deadline)
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    )
+}
+
+fn create_lmsr_market(s: &TestSetup, b: i128) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Lmsr(b),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    )
 }
 
 // ===== Initialization Tests =====
@@ -59,295 +125,861 @@ fn test_initialize_twice_fails() {
     assert!(result.is_err());
 }
 
-// ===== Create Market Tests =====
+/// A deployment whose `initialize` has not been called yet.
+fn setup_uninitialized() -> (Env, PredictionMarketClient<'static>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PredictionMarket, ());
+    let client = PredictionMarketClient::new(&env, &contract_id);
+    (env, client)
+}
 
 #[test]
-fn test_create_market() {
-    let s = setup();
-    let market_id = create_test_market(&s, 3600);
-    assert_eq!(market_id, 0);
-    assert_eq!(s.client.get_market_count(), 1);
+fn test_admin_calls_before_initialize_fail() {
+    let (env, client) = setup_uninitialized();
+    let account = Address::generate(&env);
+    let err = Error::NotInitialized;
 
-    let market = s.client.get_market(&market_id);
-    assert_eq!(market.status, MarketStatus::Open);
-    assert_eq!(market.total_yes, 0);
-    assert_eq!(market.total_no, 0);
-    assert_eq!(market.pool_balance, 0);
+    assert_eq!(client.try_get_admin(), Err(Ok(err)));
+    assert_eq!(client.try_has_role(&Role::Pauser, &account), Err(Ok(err)));
+    assert_eq!(client.try_set_resolver(&0, &account), Err(Ok(err)));
+    assert_eq!(client.try_upgrade(&BytesN::from_array(&env, &[0; 32])), Err(Ok(err)));
+    assert_eq!(client.try_migrate(&10), Err(Ok(err)));
+    assert_eq!(client.try_grant_role(&Role::Pauser, &account), Err(Ok(err)));
+    assert_eq!(client.try_revoke_role(&Role::Pauser, &account), Err(Ok(err)));
+    assert_eq!(client.try_set_permissionless_creation(&false), Err(Ok(err)));
+    assert_eq!(client.try_propose_admin(&account), Err(Ok(err)));
+    assert_eq!(client.try_cancel_admin_transfer(), Err(Ok(err)));
+    assert_eq!(client.try_allow_token(&account), Err(Ok(err)));
+    assert_eq!(client.try_disallow_token(&account), Err(Ok(err)));
+    assert_eq!(client.try_set_dispute_params(&1, &1), Err(Ok(err)));
+    assert_eq!(client.try_set_claim_window(&1), Err(Ok(err)));
+    assert_eq!(client.try_sweep_market(&0, &account), Err(Ok(err)));
 }
 
 #[test]
-fn test_create_multiple_markets() {
-    let s = setup();
-    let id0 = create_test_market(&s, 3600);
-    let id1 = create_test_market(&s, 7200);
-    assert_eq!(id0, 0);
-    assert_eq!(id1, 1);
-    assert_eq!(s.client.get_market_count(), 2);
+fn test_role_calls_before_initialize_fail() {
+    let (env, client) = setup_uninitialized();
+    let caller = Address::generate(&env);
+    let err = Error::NotInitialized;
+
+    assert_eq!(client.try_pause(&caller), Err(Ok(err)));
+    assert_eq!(client.try_unpause(&caller), Err(Ok(err)));
+    assert_eq!(client.try_freeze_market(&caller, &0), Err(Ok(err)));
+    assert_eq!(client.try_unfreeze_market(&caller, &0), Err(Ok(err)));
+    assert_eq!(client.try_set_protocol_fee(&caller, &100), Err(Ok(err)));
+    assert_eq!(client.try_withdraw_protocol_fees(&caller, &caller, &caller), Err(Ok(err)));
+
+    // With no token or admin to default to, no market can be created
+    let deadline = env.ledger().timestamp() + 3600;
+    let result = client.try_create_market(
+        &caller,
+        &String::from_str(&env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(err)));
+
+    // so calls on a market fail cleanly rather than trapping
+    let result = client.try_buy_shares(&caller, &0, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketNotFound)));
+    let result = client.try_resolve_market(&caller, &0, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::MarketNotFound)));
+}
+
+// ===== Upgrade Tests =====
+
+// Release builds pinned at each schema version. The upgrade tests deploy
+// one and move it to a newer build, or to this one.
+mod v1 {
+    soroban_sdk::contractimport!(file = "testdata/prediction_market_v1.wasm");
+}
+
+mod v2 {
+    soroban_sdk::contractimport!(file = "testdata/prediction_market_v2.wasm");
+}
+
+mod v3 {
+    soroban_sdk::contractimport!(file = "testdata/prediction_market_v3.wasm");
 }
 
+// Network per-transaction limits the batch entry points are checked against.
+const TX_MAX_INSTRUCTIONS: i64 = 100_000_000;
+const TX_MAX_READ_ENTRIES: u32 = 40;
+const TX_MAX_WRITE_ENTRIES: u32 = 25;
+
 #[test]
-fn test_create_market_past_deadline_fails() {
-    let s = setup();
-    // Set ledger timestamp to 1000
-    s.env.ledger().with_mut(|li| li.timestamp = 1000);
-    let result = s.client.try_create_market(
-        &s.admin,
-        &String::from_str(&s.env, "Test?"),
-        &500, // past deadline
-    );
-    assert!(result.is_err());
+fn test_upgrade_preserves_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = token::Client::new(&env, &sac.address());
+    let token_admin = token::StellarAssetClient::new(&env, &sac.address());
+
+    // Seed state through the v1 build
+    let contract_id = env.register(v1::WASM, ());
+    let old = v1::Client::new(&env, &contract_id);
+    old.initialize(&admin, &sac.address());
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    token_admin.mint(&alice, &10_000_000);
+    token_admin.mint(&bob, &30_000_000);
+
+    let question = String::from_str(&env, "Will it rain tomorrow?");
+    let deadline = env.ledger().timestamp() + 3600;
+    let create = |d: u64| {
+        old.create_market(
+            &admin,
+            &question,
+            &d,
+            &v1::MarketKind::Parimutuel,
+            &None,
+            &None,
+            &0,
+            &None,
+        )
+    };
+    let settled_id = create(deadline);
+    let open_id = create(deadline + 86_400);
+    old.buy_shares(&alice, &settled_id, &v1::Side::Yes, &10_000_000);
+    old.buy_shares(&bob, &settled_id, &v1::Side::No, &30_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    old.resolve_market(&settled_id, &v1::Side::Yes);
+
+    // Swap in the v2 build and migrate
+    let wasm_hash = env.deployer().upload_contract_wasm(v2::WASM);
+    old.upgrade(&wasm_hash);
+    assert_eq!(env.auths()[0].0, admin);
+
+    let new = v2::Client::new(&env, &contract_id);
+    assert_eq!(new.get_schema_version(), 1);
+    assert_eq!(new.migrate(), 2);
+    assert_eq!(new.get_schema_version(), 2);
+
+    // Entries written by v1 read back unchanged
+    let market = new.get_market(&settled_id);
+    assert_eq!(market.question, question);
+    assert_eq!(market.status, v2::MarketStatus::Resolved(v2::Side::Yes));
+    assert_eq!(market.pool_balance, 40_000_000);
+    assert_eq!(new.get_position(&settled_id, &alice).yes_shares, 10_000_000);
+    assert_eq!(new.get_admin(), admin);
+    assert_eq!(new.get_market_count(), 2);
+
+    // The listing indexes introduced in v2 were backfilled
+    assert_eq!(new.list_markets_by_creator(&admin, &0, &10).len(), 2);
+    let settled = new.list_markets_by_status(&v2::StatusFilter::Resolved, &0, &10);
+    assert_eq!(settled.get_unchecked(0).0, settled_id);
+    let open = new.list_markets_by_status(&v2::StatusFilter::Open, &0, &10);
+    assert_eq!(open.len(), 1);
+    assert_eq!(open.get_unchecked(0).0, open_id);
+
+    // And the upgraded code keeps paying out
+    assert_eq!(new.claim_winnings(&alice, &settled_id), 40_000_000);
+    assert_eq!(token.balance(&alice), 40_000_000);
+
+    let result = new.try_migrate();
+    assert_eq!(result, Err(Ok(Error::AlreadyMigrated.into())));
 }
 
-// ===== Buy Shares Tests =====
+#[test]
+fn test_migrate_from_v2_adds_claim_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let token_admin = token::StellarAssetClient::new(&env, &sac.address());
+
+    // Seed state through the v2 build
+    let contract_id = env.register(v2::WASM, ());
+    let old = v2::Client::new(&env, &contract_id);
+    old.initialize(&admin, &sac.address());
+
+    let alice = Address::generate(&env);
+    token_admin.mint(&alice, &10_000_000);
+    let question = String::from_str(&env, "Will it rain tomorrow?");
+    let deadline = env.ledger().timestamp() + 3600;
+    let create = |d: u64| {
+        old.create_market(
+            &admin,
+            &question,
+            &d,
+            &v2::MarketKind::Parimutuel,
+            &None,
+            &None,
+            &0,
+            &None,
+        )
+    };
+    let settled_id = create(deadline);
+    let open_id = create(deadline + 86_400);
+    old.buy_shares(&alice, &settled_id, &v2::Side::Yes, &10_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    old.resolve_market(&admin, &settled_id, &v2::Side::Yes);
+
+    // Swap in this build and migrate
+    env.register_at(&contract_id, PredictionMarket, ());
+    let new = PredictionMarketClient::new(&env, &contract_id);
+    assert_eq!(new.get_schema_version(), 2);
+    assert_eq!(new.migrate(&10), 4);
+
+    // Markets settled before the upgrade never expire; open ones get the
+    // default window
+    let settled = new.get_market(&settled_id);
+    assert_eq!(settled.claim_window, 0);
+    assert_eq!(settled.claimed_total, 0);
+    assert_eq!(settled.pool_balance, 10_000_000);
+    assert_eq!(new.get_market(&open_id).claim_window, new.get_claim_window());
+
+    // Liabilities start from the balance held at migration
+    let solvency = new.get_solvency();
+    assert_eq!(solvency.len(), 1);
+    assert_eq!(solvency.get_unchecked(0).liabilities, 10_000_000);
+    assert_eq!(solvency.get_unchecked(0).balance, 10_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 10 * 31_536_000);
+    assert_eq!(new.claim_winnings(&alice, &settled_id), 10_000_000);
+    assert_eq!(new.get_solvency().get_unchecked(0).liabilities, 0);
+    let result = new.try_sweep_market(&settled_id, &admin);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+}
 
 #[test]
-fn test_buy_yes_shares() {
-    let s = setup();
-    let alice = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &10_000_000);
+fn test_migrate_from_v3_books_paid_out() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    let market_id = create_test_market(&s, 3600);
-    s.client
-        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = token::Client::new(&env, &sac.address());
+    let token_admin = token::StellarAssetClient::new(&env, &sac.address());
 
-    let market = s.client.get_market(&market_id);
-    assert_eq!(market.total_yes, 10_000_000);
-    assert_eq!(market.total_no, 0);
-    assert_eq!(market.pool_balance, 10_000_000);
+    // Seed state through the v3 build
+    let contract_id = env.register(v3::WASM, ());
+    let old = v3::Client::new(&env, &contract_id);
+    old.initialize(&admin, &sac.address());
+    old.set_claim_window(&100);
 
-    let pos = s.client.get_position(&market_id, &alice);
-    assert_eq!(pos.yes_shares, 10_000_000);
-    assert_eq!(pos.no_shares, 0);
-    assert_eq!(pos.claimed, false);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    token_admin.mint(&alice, &25_000_000);
+    token_admin.mint(&bob, &30_000_000);
+    let question = String::from_str(&env, "Will it rain tomorrow?");
+    let deadline = env.ledger().timestamp() + 3600;
+    let create = |d: u64| {
+        old.create_market(
+            &admin,
+            &question,
+            &d,
+            &v3::MarketKind::Parimutuel,
+            &None,
+            &None,
+            &0,
+            &None,
+        )
+    };
+    let claimed_id = create(deadline);
+    let swept_id = create(deadline);
+    let open_id = create(deadline + 86_400);
+    old.buy_shares(&alice, &claimed_id, &v3::Side::Yes, &10_000_000);
+    old.buy_shares(&bob, &claimed_id, &v3::Side::No, &30_000_000);
+    old.buy_shares(&alice, &swept_id, &v3::Side::Yes, &10_000_000);
+    old.buy_shares(&alice, &open_id, &v3::Side::Yes, &5_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    old.resolve_market(&admin, &claimed_id, &v3::Side::Yes);
+    old.resolve_market(&admin, &swept_id, &v3::Side::Yes);
+    old.claim_winnings(&alice, &claimed_id);
+    env.ledger().with_mut(|li| li.timestamp += 101);
+    old.sweep_market(&swept_id, &admin);
 
-    assert_eq!(s.token.balance(&alice), 0);
+    // Swap in this build and migrate
+    env.register_at(&contract_id, PredictionMarket, ());
+    let new = PredictionMarketClient::new(&env, &contract_id);
+    assert_eq!(new.get_schema_version(), 3);
+    assert_eq!(new.migrate(&10), 4);
+
+    // Claims and sweeps made under v3 count as paid out
+    let claimed = new.get_market(&claimed_id);
+    assert_eq!(claimed.claimed_total, 40_000_000);
+    assert_eq!(claimed.total_paid_out, 40_000_000);
+    assert_eq!(claimed.claim_window, 100);
+    let swept = new.get_market(&swept_id);
+    assert_eq!(swept.total_paid_out, swept.pool_balance);
+    let result = new.try_sweep_market(&claimed_id, &admin);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+
+    // Trading on existing markets runs until the deadline, as before
+    let open = new.get_market(&open_id);
+    assert_eq!(open.trading_closes_at, deadline + 86_400);
+    // Markets resolved by the admin follow later admin transfers
+    assert_eq!(open.resolver, None);
+    assert_eq!(open.total_paid_out, 0);
+    let solvency = new.get_solvency();
+    assert_eq!(solvency.get_unchecked(0).liabilities, 5_000_000);
+    assert_eq!(token.balance(&contract_id), 5_000_000);
 }
 
 #[test]
-fn test_buy_no_shares() {
-    let s = setup();
-    let bob = Address::generate(&s.env);
-    s.token_admin.mint(&bob, &20_000_000);
+fn test_migrate_in_batches() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    let market_id = create_test_market(&s, 3600);
-    s.client
-        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
 
-    let market = s.client.get_market(&market_id);
-    assert_eq!(market.total_yes, 0);
-    assert_eq!(market.total_no, 20_000_000);
-    assert_eq!(market.pool_balance, 20_000_000);
+    // Seed more markets through the v2 build than one call can rewrite
+    let contract_id = env.register(v2::WASM, ());
+    let old = v2::Client::new(&env, &contract_id);
+    old.initialize(&admin, &sac.address());
+    let question = String::from_str(&env, "Will it rain tomorrow?");
+    let deadline = env.ledger().timestamp() + 3600;
+    for _ in 0..30 {
+        old.create_market(
+            &admin,
+            &question,
+            &deadline,
+            &v2::MarketKind::Parimutuel,
+            &None,
+            &None,
+            &0,
+            &None,
+        );
+    }
+
+    env.register_at(&contract_id, PredictionMarket, ());
+    let new = PredictionMarketClient::new(&env, &contract_id);
+
+    // Two steps over 30 markets take six calls of 10; each fits the limits
+    // and the schema version only moves once a step covers every market
+    let expected = [2, 2, 3, 3, 3, 4];
+    for (call, version) in expected.iter().enumerate() {
+        if call > 0 && call < 5 {
+            let result = new.try_get_market(&0).err();
+            assert_eq!(result, Some(Ok(Error::MigrationPending)));
+        }
+        assert_eq!(new.migrate(&10), *version);
+        // Only calls that finish a step announce it
+        let events = env.events().all();
+        if call == 2 || call == 5 {
+            let migrated: Migrated = events.last().unwrap().2.into_val(&env);
+            assert_eq!(migrated.to_schema, *version);
+        } else {
+            assert!(events.is_empty());
+        }
+        let resources = env.cost_estimate().resources();
+        assert!(resources.instructions < TX_MAX_INSTRUCTIONS);
+        assert!(resources.read_entries < TX_MAX_READ_ENTRIES);
+        assert!(resources.write_entries < TX_MAX_WRITE_ENTRIES);
+        assert_eq!(new.get_schema_version(), *version);
+    }
+
+    assert_eq!(new.get_market(&29).trading_closes_at, deadline);
+    let result = new.try_migrate(&10);
+    assert_eq!(result, Err(Ok(Error::AlreadyMigrated)));
 }
 
 #[test]
-fn test_buy_multiple_times() {
+fn test_migrate_zero_limit_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let contract_id = env.register(v3::WASM, ());
+    v3::Client::new(&env, &contract_id).initialize(&admin, &sac.address());
+
+    env.register_at(&contract_id, PredictionMarket, ());
+    let new = PredictionMarketClient::new(&env, &contract_id);
+    assert_eq!(new.try_migrate(&0), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(new.migrate(&1), 4);
+}
+
+#[test]
+fn test_migrate_when_current_fails() {
     let s = setup();
-    let alice = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &30_000_000);
+    assert_eq!(s.client.get_schema_version(), 4);
 
-    let market_id = create_test_market(&s, 3600);
-    s.client
-        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
-    s.client
-        .buy_shares(&alice, &market_id, &Side::No, &20_000_000);
+    let result = s.client.try_migrate(&10);
+    assert_eq!(result, Err(Ok(Error::AlreadyMigrated)));
+}
 
-    let pos = s.client.get_position(&market_id, &alice);
-    assert_eq!(pos.yes_shares, 10_000_000);
-    assert_eq!(pos.no_shares, 20_000_000);
+#[test]
+fn test_unversioned_storage_reads_as_v1() {
+    let s = setup();
+    create_test_market(&s, 3600);
+
+    // The first release stored no version at all
+    s.env.as_contract(&s.client.address, || {
+        s.env.storage().instance().remove(&DataKey::SchemaVersion);
+    });
+    assert_eq!(s.client.get_schema_version(), 1);
+    let result = s.client.try_get_market(&0).err();
+    assert_eq!(result, Some(Ok(Error::MigrationPending)));
 }
 
+// ===== Pause Tests =====
+
 #[test]
-fn test_buy_after_deadline_fails() {
+fn test_pause_blocks_trading() {
     let s = setup();
     let alice = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &10_000_000);
-
+    s.token_admin.mint(&alice, &20_000_000);
     let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
 
-    // Advance past deadline
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.pause(&s.admin);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    let event = s.env.events().all().last().unwrap();
+    assert_eq!(event.1, (symbol_short!("pause"),).into_val(&s.env));
+    assert!(s.client.is_paused());
 
     let result = s
         .client
         .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(Error::Paused)));
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &10_000_000, &0);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+
+    s.client.unpause(&s.admin);
+    let event = s.env.events().all().last().unwrap();
+    assert_eq!(event.1, (symbol_short!("unpause"),).into_val(&s.env));
+    assert!(!s.client.is_paused());
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
 }
 
 #[test]
-fn test_buy_zero_amount_fails() {
+fn test_pause_blocks_resolution_and_claims() {
     let s = setup();
     let alice = Address::generate(&s.env);
-
+    s.token_admin.mint(&alice, &10_000_000);
     let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+
+    s.client.pause(&s.admin);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::Paused)));
     let result = s
         .client
-        .try_buy_shares(&alice, &market_id, &Side::Yes, &0);
-    assert!(result.is_err());
+        .try_propose_outcome(&alice, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+
+    s.client.unpause(&s.admin);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    s.client.pause(&s.admin);
+    let result = s.client.try_claim_winnings(&alice, &market_id);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+
+    s.client.unpause(&s.admin);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
 }
 
 #[test]
-fn test_buy_nonexistent_market_fails() {
+fn test_freeze_market_only_affects_that_market() {
     let s = setup();
     let alice = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&alice, &20_000_000);
+    let frozen_id = create_test_market(&s, 3600);
+    let other_id = create_test_market(&s, 3600);
+
+    s.client.freeze_market(&s.admin, &frozen_id);
+    let event = s.env.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        (symbol_short!("pause"), frozen_id).into_val(&s.env)
+    );
+    assert!(s.client.is_market_frozen(&frozen_id));
+    assert!(!s.client.is_market_frozen(&other_id));
+    assert!(!s.client.is_paused());
 
     let result = s
         .client
-        .try_buy_shares(&alice, &999, &Side::Yes, &10_000_000);
-    assert!(result.is_err());
-}
+        .try_buy_shares(&alice, &frozen_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+    s.client
+        .buy_shares(&alice, &other_id, &Side::Yes, &10_000_000);
 
-// ===== Resolve Market Tests =====
+    // Lifting a global pause leaves the freeze in place
+    s.client.pause(&s.admin);
+    s.client.unpause(&s.admin);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &frozen_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+
+    s.client.unfreeze_market(&s.admin, &frozen_id);
+    assert!(!s.client.is_market_frozen(&frozen_id));
+    s.client
+        .buy_shares(&alice, &frozen_id, &Side::Yes, &10_000_000);
+}
 
 #[test]
-fn test_resolve_market() {
+fn test_pause_suspends_dispute_window() {
     let s = setup();
-    let market_id = create_test_market(&s, 3600);
-
-    // Advance past deadline
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+    s.client.propose_outcome(&proposer, &market_id, &Side::Yes);
+    s.env.ledger().with_mut(|li| li.timestamp += 3600);
 
-    s.client.resolve_market(&market_id, &Side::Yes);
+    // Disputes are blocked while paused, so the window stands still
+    s.client.pause(&s.admin);
+    let clock = s.client.get_clock();
+    s.env.ledger().with_mut(|li| li.timestamp += 86_400);
+    assert_eq!(s.client.get_clock(), clock);
+    let result = s.client.try_dispute(&disputer, &market_id);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+    s.client.unpause(&s.admin);
 
-    let market = s.client.get_market(&market_id);
-    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+    let result = s.client.try_finalize_proposal(&market_id);
+    assert_eq!(result, Err(Ok(Error::DisputeWindowOpen)));
+    s.client.dispute(&disputer, &market_id);
+    assert_eq!(s.client.get_market(&market_id).status, MarketStatus::Disputed);
 }
 
 #[test]
-fn test_resolve_before_deadline_fails() {
+fn test_pause_suspends_claim_window_and_sweeps() {
     let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.client.set_claim_window(&86_400);
     let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
 
-    let result = s.client.try_resolve_market(&market_id, &Side::Yes);
-    assert!(result.is_err());
+    s.client.pause(&s.admin);
+    s.env.ledger().with_mut(|li| li.timestamp += 2 * 86_400);
+    let result = s.client.try_sweep_market(&market_id, &s.admin);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+    s.client.unpause(&s.admin);
+
+    // The day spent paused does not count against the claim window
+    let result = s.client.try_sweep_market(&market_id, &s.admin);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
 }
 
 #[test]
-fn test_resolve_already_resolved_fails() {
+fn test_freeze_extends_windows() {
     let s = setup();
-    let market_id = create_test_market(&s, 3600);
+    let (proposed_id, proposer, disputer) = setup_proposal_market(&s);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.client.set_claim_window(&86_400);
+    let settled_id = create_test_market(&s, 1);
+    s.client.buy_shares(&alice, &settled_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 2);
+    s.client.resolve_market(&s.admin, &settled_id, &Side::Yes);
+    s.client.propose_outcome(&proposer, &proposed_id, &Side::Yes);
 
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.freeze_market(&s.admin, &proposed_id);
+    s.client.freeze_market(&s.admin, &settled_id);
+    s.env.ledger().with_mut(|li| li.timestamp += 2 * 86_400);
+    let result = s.client.try_sweep_market(&settled_id, &s.admin);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+    s.client.unfreeze_market(&s.admin, &proposed_id);
+    s.client.unfreeze_market(&s.admin, &settled_id);
 
-    s.client.resolve_market(&market_id, &Side::Yes);
-    let result = s.client.try_resolve_market(&market_id, &Side::No);
-    assert!(result.is_err());
+    s.client.dispute(&disputer, &proposed_id);
+    let result = s.client.try_sweep_market(&settled_id, &s.admin);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+    assert_eq!(s.client.claim_winnings(&alice, &settled_id), 10_000_000);
 }
 
-// ===== Claim Winnings Tests =====
-
 #[test]
-fn test_winner_takes_all() {
+fn test_invalid_refunds_work_while_paused() {
     let s = setup();
     let alice = Address::generate(&s.env);
     let bob = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &10_000_000); // 1 XLM
-    s.token_admin.mint(&bob, &20_000_000); // 2 XLM
-
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
     let market_id = create_test_market(&s, 3600);
-
-    // Alice bets YES, Bob bets NO
     s.client
         .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
     s.client
         .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
 
-    // Resolve as YES
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
-    s.client.resolve_market(&market_id, &Side::Yes);
+    s.client.pause(&s.admin);
+    s.client.freeze_market(&s.admin, &market_id);
+    s.client.invalidate_market(&s.admin, &market_id);
 
-    // Alice wins entire pool (30M stroops = 3 XLM)
-    let payout = s.client.claim_winnings(&alice, &market_id);
-    assert_eq!(payout, 30_000_000);
-    assert_eq!(s.token.balance(&alice), 30_000_000);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 20_000_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+}
 
-    // Bob gets nothing
-    let payout_bob = s.client.claim_winnings(&bob, &market_id);
-    assert_eq!(payout_bob, 0);
-    assert_eq!(s.token.balance(&bob), 0);
+// ===== Role Tests =====
+
+#[test]
+fn test_admin_holds_every_role() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+    for role in [Role::MarketCreator, Role::Resolver, Role::Pauser, Role::FeeManager] {
+        assert!(s.client.has_role(&role, &s.admin));
+        assert!(!s.client.has_role(&role, &stranger));
+    }
 }
 
 #[test]
-fn test_proportional_payout() {
+fn test_grant_and_revoke_role() {
     let s = setup();
-    let alice = Address::generate(&s.env);
-    let bob = Address::generate(&s.env);
-    let charlie = Address::generate(&s.env);
+    let ops = Address::generate(&s.env);
 
-    s.token_admin.mint(&alice, &10_000_000); // 1 XLM
-    s.token_admin.mint(&bob, &20_000_000); // 2 XLM
-    s.token_admin.mint(&charlie, &30_000_000); // 3 XLM
+    s.client.grant_role(&Role::Pauser, &ops);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    let event = s.env.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        (symbol_short!("grant"), ops.clone()).into_val(&s.env)
+    );
+    assert!(s.client.has_role(&Role::Pauser, &ops));
+    assert!(!s.client.has_role(&Role::FeeManager, &ops));
+
+    s.client.pause(&ops);
+    assert_eq!(s.env.auths()[0].0, ops);
+    s.client.unpause(&ops);
+
+    s.client.revoke_role(&Role::Pauser, &ops);
+    assert!(!s.client.has_role(&Role::Pauser, &ops));
+    let result = s.client.try_pause(&ops);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
 
+#[test]
+fn test_privileged_calls_reject_missing_role() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
     let market_id = create_test_market(&s, 3600);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
 
-    // Alice: 1 XLM YES, Bob: 2 XLM YES, Charlie: 3 XLM NO
-    s.client
-        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    // Holding one role does not grant the others
+    s.client.grant_role(&Role::Pauser, &stranger);
+
+    let result = s.client.try_set_protocol_fee(&stranger, &100);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let result = s
+        .client
+        .try_withdraw_protocol_fees(&stranger, &s.token.address, &stranger);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let result = s
+        .client
+        .try_resolve_market(&stranger, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let result = s.client.try_invalidate_market(&stranger, &market_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_resolver_role_resolves_any_market() {
+    let s = setup();
+    let desk = Address::generate(&s.env);
+    let oracle_team = Address::generate(&s.env);
+    let market_id = create_market_with_resolver(&s, &desk);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+
+    s.client.grant_role(&Role::Resolver, &oracle_team);
     s.client
-        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+        .resolve_market(&oracle_team, &market_id, &Side::No);
+    assert_eq!(s.env.auths()[0].0, oracle_team);
+    assert_eq!(
+        s.client.get_market(&market_id).status,
+        MarketStatus::Resolved(Side::No)
+    );
+}
+
+#[test]
+fn test_fee_manager_sets_and_withdraws_fees() {
+    let s = setup();
+    let treasurer = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    s.client.grant_role(&Role::FeeManager, &treasurer);
+    s.client.set_protocol_fee(&treasurer, &100);
+    assert_eq!(s.env.auths()[0].0, treasurer);
+
+    let market_id = create_test_market(&s, 3600);
     s.client
-        .buy_shares(&charlie, &market_id, &Side::No, &30_000_000);
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
 
-    // Pool = 6 XLM. Resolve YES. Total YES = 3 XLM.
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
-    s.client.resolve_market(&market_id, &Side::Yes);
+    let amount = s
+        .client
+        .withdraw_protocol_fees(&treasurer, &s.token.address, &treasurer);
+    assert_eq!(s.env.auths()[0].0, treasurer);
+    assert_eq!(amount, 100_000);
+    assert_eq!(s.token.balance(&treasurer), 100_000);
+}
 
-    // Alice: 1/3 of 60M = 20M stroops
-    let payout_alice = s.client.claim_winnings(&alice, &market_id);
-    assert_eq!(payout_alice, 20_000_000);
+#[test]
+fn test_market_creator_role_when_creation_restricted() {
+    let s = setup();
+    let creator = Address::generate(&s.env);
+    assert!(s.client.is_permissionless_creation());
+    create_market_with_fee(&s, &creator, 0);
 
-    // Bob: 2/3 of 60M = 40M stroops
-    let payout_bob = s.client.claim_winnings(&bob, &market_id);
-    assert_eq!(payout_bob, 40_000_000);
+    s.client.set_permissionless_creation(&false);
+    assert!(!s.client.is_permissionless_creation());
 
-    // Charlie: 0
-    let payout_charlie = s.client.claim_winnings(&charlie, &market_id);
-    assert_eq!(payout_charlie, 0);
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &creator,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    // The admin can always create
+    create_test_market(&s, 3600);
+
+    s.client.grant_role(&Role::MarketCreator, &creator);
+    let market_id = create_market_with_fee(&s, &creator, 0);
+    assert_eq!(s.client.get_market(&market_id).creator, creator);
 }
 
+// ===== Admin Transfer Tests =====
+
 #[test]
-fn test_no_winners_refund() {
+fn test_admin_transfer() {
     let s = setup();
-    let alice = Address::generate(&s.env);
-    let bob = Address::generate(&s.env);
+    let new_admin = Address::generate(&s.env);
+    assert_eq!(s.client.get_admin(), s.admin);
 
-    s.token_admin.mint(&alice, &10_000_000);
-    s.token_admin.mint(&bob, &20_000_000);
+    s.client.propose_admin(&new_admin);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(s.client.get_pending_admin(), Some(new_admin.clone()));
+    // Nothing changes until the nominee accepts
+    assert_eq!(s.client.get_admin(), s.admin);
+
+    s.client.accept_admin();
+    assert_eq!(s.env.auths()[0].0, new_admin);
+    assert_eq!(s.client.get_admin(), new_admin);
+    assert_eq!(s.client.get_pending_admin(), None);
+
+    // Admin-only calls now need the new key
+    s.client.set_protocol_fee(&new_admin, &50);
+    assert_eq!(s.env.auths()[0].0, new_admin);
+}
 
+#[test]
+fn test_cancel_admin_transfer() {
+    let s = setup();
+    let new_admin = Address::generate(&s.env);
+
+    let result = s.client.try_cancel_admin_transfer();
+    assert_eq!(result, Err(Ok(Error::NoPendingAdmin)));
+
+    s.client.propose_admin(&new_admin);
+    s.client.cancel_admin_transfer();
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(s.client.get_pending_admin(), None);
+
+    let result = s.client.try_accept_admin();
+    assert_eq!(result, Err(Ok(Error::NoPendingAdmin)));
+    assert_eq!(s.client.get_admin(), s.admin);
+}
+
+#[test]
+fn test_propose_admin_replaces_nominee() {
+    let s = setup();
+    let first = Address::generate(&s.env);
+    let second = Address::generate(&s.env);
+
+    s.client.propose_admin(&first);
+    s.client.propose_admin(&second);
+    s.client.accept_admin();
+    assert_eq!(s.env.auths()[0].0, second);
+    assert_eq!(s.client.get_admin(), second);
+}
+
+// ===== Create Market Tests =====
+
+#[test]
+fn test_create_market() {
+    let s = setup();
     let market_id = create_test_market(&s, 3600);
+    assert_eq!(market_id, 0);
+    assert_eq!(s.client.get_market_count(), 1);
 
-    // Both bet YES
-    s.client
-        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
-    s.client
-        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Open);
+    assert_eq!(market.total_yes, 0);
+    assert_eq!(market.total_no, 0);
+    assert_eq!(market.pool_balance, 0);
+    assert_eq!(market.trading_closes_at, market.deadline);
+}
 
-    // Resolve as NO -- no winners
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
-    s.client.resolve_market(&market_id, &Side::No);
+#[test]
+fn test_create_multiple_markets() {
+    let s = setup();
+    let id0 = create_test_market(&s, 3600);
+    let id1 = create_test_market(&s, 7200);
+    assert_eq!(id0, 0);
+    assert_eq!(id1, 1);
+    assert_eq!(s.client.get_market_count(), 2);
+}
 
-    // Both get proportional refund
-    let payout_alice = s.client.claim_winnings(&alice, &market_id);
-    assert_eq!(payout_alice, 10_000_000); // 1/3 of 30M
+#[test]
+fn test_create_market_past_deadline_fails() {
+    let s = setup();
+    // Set ledger timestamp to 1000
+    s.env.ledger().with_mut(|li| li.timestamp = 1000);
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &500, // past deadline
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert!(result.is_err());
+}
 
-    let payout_bob = s.client.claim_winnings(&bob, &market_id);
-    assert_eq!(payout_bob, 20_000_000); // 2/3 of 30M
+#[test]
+fn test_create_market_invalid_trading_close_fails() {
+    let s = setup();
+    s.env.ledger().with_mut(|li| li.timestamp = 1000);
+    for closes_at in [1000, 5000] {
+        let result = s.client.try_create_market(
+            &s.admin,
+            &String::from_str(&s.env, "Test?"),
+            &4600,
+            &MarketKind::Parimutuel,
+            &None,
+            &None,
+            &0,
+            &None,
+            &Some(closes_at),
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidDeadline)));
+    }
 }
 
+// ===== Buy Shares Tests =====
+
 #[test]
-fn test_double_claim_fails() {
+fn test_buy_yes_shares() {
     let s = setup();
     let alice = Address::generate(&s.env);
     s.token_admin.mint(&alice, &10_000_000);
@@ -356,70 +988,3290 @@ fn test_double_claim_fails() {
     s.client
         .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
 
-    s.env
-        .ledger()
-        .with_mut(|li| li.timestamp = li.timestamp + 3601);
-    s.client.resolve_market(&market_id, &Side::Yes);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 10_000_000);
+    assert_eq!(market.total_no, 0);
+    assert_eq!(market.pool_balance, 10_000_000);
 
-    s.client.claim_winnings(&alice, &market_id);
-    let result = s.client.try_claim_winnings(&alice, &market_id);
-    assert!(result.is_err());
+    let pos = s.client.get_position(&market_id, &alice);
+    assert_eq!(pos.yes_shares, 10_000_000);
+    assert_eq!(pos.no_shares, 0);
+    assert_eq!(pos.claimed, false);
+
+    assert_eq!(s.token.balance(&alice), 0);
 }
 
 #[test]
-fn test_claim_from_open_market_fails() {
+fn test_buy_no_shares() {
+    let s = setup();
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 0);
+    assert_eq!(market.total_no, 20_000_000);
+    assert_eq!(market.pool_balance, 20_000_000);
+}
+
+#[test]
+fn test_buy_multiple_times() {
     let s = setup();
     let alice = Address::generate(&s.env);
-    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&alice, &30_000_000);
 
     let market_id = create_test_market(&s, 3600);
     s.client
         .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::No, &20_000_000);
 
-    let result = s.client.try_claim_winnings(&alice, &market_id);
-    assert!(result.is_err());
+    let pos = s.client.get_position(&market_id, &alice);
+    assert_eq!(pos.yes_shares, 10_000_000);
+    assert_eq!(pos.no_shares, 20_000_000);
 }
 
 #[test]
-fn test_claim_with_no_position() {
+fn test_buy_after_deadline_fails() {
     let s = setup();
     let alice = Address::generate(&s.env);
-    let bob = Address::generate(&s.env);
     s.token_admin.mint(&alice, &10_000_000);
 
     let market_id = create_test_market(&s, 3600);
-    s.client
-        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
 
+    // Advance past deadline
     s.env
         .ledger()
         .with_mut(|li| li.timestamp = li.timestamp + 3601);
-    s.client.resolve_market(&market_id, &Side::Yes);
 
-    // Bob has no position, payout should be 0
-    let payout = s.client.claim_winnings(&bob, &market_id);
-    assert_eq!(payout, 0);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert!(result.is_err());
 }
 
-// ===== View Function Tests =====
+#[test]
+fn test_trading_closes_before_deadline() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let closes_at = deadline - 600;
+    let market_id = s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &Some(closes_at),
+    );
+    assert_eq!(s.client.get_market(&market_id).trading_closes_at, closes_at);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    // Inside the lock period nothing trades, but the market is not yet due
+    s.env.ledger().with_mut(|li| li.timestamp = closes_at);
+    let open = s.client.list_markets_by_status(&StatusFilter::Open, &0, &10);
+    assert_eq!(open.len(), 0);
+    let waiting = s
+        .client
+        .list_markets_by_status(&StatusFilter::AwaitingResolution, &0, &10);
+    assert_eq!(waiting.len(), 1);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &10_000_000, &0);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+    let result = s.client.try_quote_buy(&market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+    let result = s
+        .client
+        .try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::DeadlineNotReached)));
+
+    s.env.ledger().with_mut(|li| li.timestamp = deadline);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+}
 
 #[test]
-fn test_get_market_not_found() {
+fn test_buy_zero_amount_fails() {
     let s = setup();
-    let result = s.client.try_get_market(&999);
+    let alice = Address::generate(&s.env);
+
+    let market_id = create_test_market(&s, 3600);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &0);
     assert!(result.is_err());
 }
 
 #[test]
-fn test_get_position_no_position() {
+fn test_buy_nonexistent_market_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let result = s
+        .client
+        .try_buy_shares(&alice, &999, &Side::Yes, &10_000_000);
+    assert!(result.is_err());
+}
+
+// ===== Sell Shares Tests =====
+
+#[test]
+fn test_sell_shares_refunds_stake() {
     let s = setup();
     let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &30_000_000);
+
     let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &30_000_000);
+
+    let out = s
+        .client
+        .sell_shares(&alice, &market_id, &Side::Yes, &10_000_000, &10_000_000);
+    assert_eq!(out, 10_000_000);
+    assert_eq!(s.token.balance(&alice), 10_000_000);
 
     let pos = s.client.get_position(&market_id, &alice);
-    assert_eq!(pos.yes_shares, 0);
-    assert_eq!(pos.no_shares, 0);
-    assert_eq!(pos.claimed, false);
+    assert_eq!(pos.yes_shares, 20_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 20_000_000);
+    assert_eq!(market.pool_balance, 20_000_000);
+}
+
+#[test]
+fn test_sell_then_payout_stays_proportional() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let charlie = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &40_000_000);
+    s.token_admin.mint(&charlie, &30_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &40_000_000);
+    s.client
+        .buy_shares(&charlie, &market_id, &Side::No, &30_000_000);
+
+    // Bob backs out of half of the stake
+    s.client
+        .sell_shares(&bob, &market_id, &Side::Yes, &20_000_000, &0);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Pool = 60M, YES = 30M: Alice 1/3, Bob 2/3
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 20_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 40_000_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+}
+
+#[test]
+fn test_sell_more_than_held_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::No, &1, &0);
+    assert_eq!(result, Err(Ok(Error::InsufficientShares)));
+
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &10_000_000, &10_000_001);
+    assert_eq!(result, Err(Ok(Error::SlippageExceeded)));
+}
+
+#[test]
+fn test_sell_after_deadline_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &10_000_000, &0);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+}
+
+#[test]
+fn test_lmsr_sell_round_trip() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &30_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &30_000_000);
+
+    // Selling straight back walks the cost function back to where it was
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    let out = s
+        .client
+        .sell_shares(&alice, &market_id, &Side::Yes, &shares, &29_999_990);
+    assert!(out <= 30_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 0);
+    assert_eq!(market.pool_balance, 69_314_719 + 50_000_000 - out);
+}
+
+// ===== Resolve Market Tests =====
+
+#[test]
+fn test_resolve_market() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+
+    // Advance past deadline
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+}
+
+#[test]
+fn test_resolve_before_deadline_fails() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_resolve_already_resolved_fails() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::No);
+    assert!(result.is_err());
+}
+
+// ===== Resolver Tests =====
+
+fn create_market_with_resolver(s: &TestSetup, resolver: &Address) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will the home team win?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &Some(resolver.clone()),
+        &0,
+        &None,
+        &None,
+    )
+}
+
+#[test]
+fn test_resolver_defaults_to_admin() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+    assert_eq!(s.client.get_market(&market_id).resolver, None);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+}
+
+#[test]
+fn test_default_resolver_follows_admin_transfer() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+    let new_admin = Address::generate(&s.env);
+    s.client.propose_admin(&new_admin);
+    s.client.accept_admin();
+
+    // The market was created under the old admin, who no longer resolves it
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let result = s.client.try_invalidate_market(&s.admin, &market_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    s.client.resolve_market(&new_admin, &market_id, &Side::Yes);
+    assert_eq!(s.env.auths()[0].0, new_admin);
+}
+
+#[test]
+fn test_custom_resolver_resolves() {
+    let s = setup();
+    let sports_desk = Address::generate(&s.env);
+    let market_id = create_market_with_resolver(&s, &sports_desk);
+    assert_eq!(s.client.get_market(&market_id).resolver, Some(sports_desk.clone()));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&sports_desk, &market_id, &Side::No);
+
+    // The delegated resolver signed, not the admin
+    let auths = s.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, sports_desk);
+}
+
+#[test]
+fn test_admin_replaces_resolver() {
+    let s = setup();
+    let old_desk = Address::generate(&s.env);
+    let new_desk = Address::generate(&s.env);
+    let market_id = create_market_with_resolver(&s, &old_desk);
+
+    s.client.set_resolver(&market_id, &new_desk);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(s.client.get_market(&market_id).resolver, Some(new_desk.clone()));
+
+    s.client.invalidate_market(&new_desk, &market_id);
+    assert_eq!(s.env.auths()[0].0, new_desk);
+}
+
+// ===== Fee Tests =====
+
+fn create_market_with_fee(s: &TestSetup, creator: &Address, creator_fee_bps: u32) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        creator,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &creator_fee_bps,
+        &None,
+        &None,
+    )
+}
+
+#[test]
+fn test_fee_limits() {
+    let s = setup();
+    let result = s.client.try_set_protocol_fee(&s.admin, &1_001);
+    assert_eq!(result, Err(Ok(Error::InvalidFee)));
+
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &1_001,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidFee)));
+}
+
+#[test]
+fn test_buy_takes_fees() {
+    let s = setup();
+    let creator = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &100_000_000);
+
+    s.client.set_protocol_fee(&s.admin, &200); // 2%
+    assert_eq!(s.client.get_protocol_fee(), 200);
+    let market_id = create_market_with_fee(&s, &creator, 100); // 1%
+
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &100_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 97_000_000);
+    assert_eq!(market.pool_balance, 97_000_000);
+    assert_eq!(s.client.get_protocol_fees(&s.token.address), 2_000_000);
+    assert_eq!(s.client.get_creator_fees(&creator, &s.token.address), 1_000_000);
+}
+
+#[test]
+fn test_payouts_plus_fees_equal_deposits() {
+    let s = setup();
+    let creator = Address::generate(&s.env);
+    let treasury = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let charlie = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    s.token_admin.mint(&charlie, &30_000_000);
+
+    s.client.set_protocol_fee(&s.admin, &150); // 1.5%
+    let market_id = create_market_with_fee(&s, &creator, 50); // 0.5%
+
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&charlie, &market_id, &Side::No, &30_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Net pool = 60M - 2% = 58.8M, split 1:2 between Alice and Bob
+    let payout_alice = s.client.claim_winnings(&alice, &market_id);
+    let payout_bob = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(payout_alice, 19_600_000);
+    assert_eq!(payout_bob, 39_200_000);
+    assert_eq!(s.client.claim_winnings(&charlie, &market_id), 0);
+
+    let protocol = s.client.withdraw_protocol_fees(&s.admin, &s.token.address, &treasury);
+    let creator_cut = s.client.withdraw_creator_fees(&creator, &s.token.address);
+    assert_eq!(protocol, 900_000);
+    assert_eq!(creator_cut, 300_000);
+
+    assert_eq!(payout_alice + payout_bob + protocol + creator_cut, 60_000_000);
+    assert_eq!(s.token.balance(&treasury), 900_000);
+    assert_eq!(s.token.balance(&creator), 300_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+
+    // Accrued balances are reset after withdrawal
+    let result = s.client.try_withdraw_creator_fees(&creator, &s.token.address);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+    assert_eq!(s.client.get_protocol_fees(&s.token.address), 0);
+}
+
+// ===== Collateral Token Tests =====
+
+fn register_token<'a>(s: &TestSetup) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
+    let issuer = Address::generate(&s.env);
+    let sac = s.env.register_stellar_asset_contract_v2(issuer);
+    (
+        token::Client::new(&s.env, &sac.address()),
+        token::StellarAssetClient::new(&s.env, &sac.address()),
+    )
+}
+
+fn create_market_in(s: &TestSetup, token: &Address, creator_fee_bps: u32) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &creator_fee_bps,
+        &Some(token.clone()),
+        &None,
+    )
+}
+
+#[test]
+fn test_default_token_is_allowed() {
+    let s = setup();
+    assert!(s.client.is_token_allowed(&s.token.address));
+
+    let market_id = create_test_market(&s, 3600);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.token, s.token.address);
+}
+
+#[test]
+fn test_create_market_with_unlisted_token_fails() {
+    let s = setup();
+    let (usdc, _) = register_token(&s);
+    assert!(!s.client.is_token_allowed(&usdc.address));
+
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &Some(usdc.address.clone()),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+}
+
+#[test]
+fn test_disallow_token_keeps_existing_markets() {
+    let s = setup();
+    let (usdc, usdc_admin) = register_token(&s);
+    let alice = Address::generate(&s.env);
+    usdc_admin.mint(&alice, &10_000_000);
+
+    s.client.allow_token(&usdc.address);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    let market_id = create_market_in(&s, &usdc.address, 0);
+
+    s.client.disallow_token(&usdc.address);
+    assert!(!s.client.is_token_allowed(&usdc.address));
+
+    // Existing market still trades in the delisted token
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert_eq!(usdc.balance(&s.client.address), 10_000_000);
+
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &Some(usdc.address.clone()),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+}
+
+#[test]
+fn test_two_tokens_in_one_contract() {
+    let s = setup();
+    let (usdc, usdc_admin) = register_token(&s);
+    s.client.allow_token(&usdc.address);
+    s.client.set_protocol_fee(&s.admin, &100); // 1%
+
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    usdc_admin.mint(&alice, &50_000_000);
+    usdc_admin.mint(&bob, &50_000_000);
+
+    let xlm_market = create_market_in(&s, &s.token.address, 0);
+    let usdc_market = create_market_in(&s, &usdc.address, 0);
+
+    s.client
+        .buy_shares(&alice, &xlm_market, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &xlm_market, &Side::No, &20_000_000);
+    s.client
+        .buy_shares(&alice, &usdc_market, &Side::No, &50_000_000);
+    s.client
+        .buy_shares(&bob, &usdc_market, &Side::Yes, &50_000_000);
+
+    // Each market's pool holds only its own token
+    assert_eq!(s.client.get_market(&xlm_market).pool_balance, 29_700_000);
+    assert_eq!(s.client.get_market(&usdc_market).pool_balance, 99_000_000);
+    assert_eq!(s.token.balance(&s.client.address), 30_000_000);
+    assert_eq!(usdc.balance(&s.client.address), 100_000_000);
+    assert_eq!(s.client.get_protocol_fees(&s.token.address), 300_000);
+    assert_eq!(s.client.get_protocol_fees(&usdc.address), 1_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &xlm_market, &Side::Yes);
+    s.client.resolve_market(&s.admin, &usdc_market, &Side::Yes);
+
+    // Alice wins the XLM market, Bob the USDC one
+    assert_eq!(s.client.claim_winnings(&alice, &xlm_market), 29_700_000);
+    assert_eq!(s.client.claim_winnings(&bob, &usdc_market), 99_000_000);
+    assert_eq!(s.token.balance(&alice), 39_700_000);
+    assert_eq!(usdc.balance(&alice), 0);
+    assert_eq!(s.token.balance(&bob), 0);
+    assert_eq!(usdc.balance(&bob), 99_000_000);
+
+    let treasury = Address::generate(&s.env);
+    s.client.withdraw_protocol_fees(&s.admin, &usdc.address, &treasury);
+    assert_eq!(usdc.balance(&treasury), 1_000_000);
+    assert_eq!(s.token.balance(&treasury), 0);
+    assert_eq!(s.client.get_protocol_fees(&s.token.address), 300_000);
+    assert_eq!(usdc.balance(&s.client.address), 0);
+}
+
+// ===== Optimistic Resolution Tests =====
+
+const BOND: i128 = 100_000_000;
+
+/// Market past its deadline with a funded proposer and disputer.
+fn setup_proposal_market(s: &TestSetup) -> (u64, Address, Address) {
+    let proposer = Address::generate(&s.env);
+    let disputer = Address::generate(&s.env);
+    s.token_admin.mint(&proposer, &BOND);
+    s.token_admin.mint(&disputer, &BOND);
+
+    let market_id = create_test_market(s, 3600);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    (market_id, proposer, disputer)
+}
+
+#[test]
+fn test_undisputed_proposal_finalizes() {
+    let s = setup();
+    let (market_id, proposer, _) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+    assert_eq!(s.client.get_market(&market_id).status, MarketStatus::Proposed);
+    assert_eq!(s.token.balance(&proposer), 0);
+
+    let result = s.client.try_finalize_proposal(&market_id);
+    assert_eq!(result, Err(Ok(Error::DisputeWindowOpen)));
+
+    // Liveness window passes without a dispute
+    s.env.ledger().with_mut(|li| li.timestamp += 7200);
+    s.client.finalize_proposal(&market_id);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+    assert_eq!(s.token.balance(&proposer), BOND);
+}
+
+#[test]
+fn test_propose_before_deadline_fails() {
+    let s = setup();
+    let proposer = Address::generate(&s.env);
+    s.token_admin.mint(&proposer, &BOND);
+    let market_id = create_test_market(&s, 3600);
+
+    let result = s
+        .client
+        .try_propose_outcome(&proposer, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::DeadlineNotReached)));
+}
+
+#[test]
+fn test_disputed_proposal_upheld() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+    s.client.dispute(&disputer, &market_id);
+    assert_eq!(s.client.get_market(&market_id).status, MarketStatus::Disputed);
+
+    let proposal = s.client.get_proposal(&market_id).unwrap();
+    assert_eq!(proposal.disputer, Some(disputer.clone()));
+
+    // Admin agrees with the proposer, who takes the disputer's bond
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(
+        s.client.get_market(&market_id).status,
+        MarketStatus::Resolved(Side::Yes)
+    );
+    assert_eq!(s.token.balance(&proposer), 2 * BOND);
+    assert_eq!(s.token.balance(&disputer), 0);
+}
+
+#[test]
+fn test_disputed_proposal_overturned() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+    s.client.dispute(&disputer, &market_id);
+
+    // Admin sides with the disputer, who takes the proposer's bond
+    s.client.resolve_market(&s.admin, &market_id, &Side::No);
+    assert_eq!(
+        s.client.get_market(&market_id).status,
+        MarketStatus::Resolved(Side::No)
+    );
+    assert_eq!(s.token.balance(&proposer), 0);
+    assert_eq!(s.token.balance(&disputer), 2 * BOND);
+}
+
+#[test]
+fn test_dispute_after_window_fails() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+    s.env.ledger().with_mut(|li| li.timestamp += 7200);
+
+    let result = s.client.try_dispute(&disputer, &market_id);
+    assert_eq!(result, Err(Ok(Error::DisputeWindowClosed)));
+}
+
+#[test]
+fn test_pending_proposal_blocks_other_resolution() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    let result = s.client.try_dispute(&disputer, &market_id);
+    assert_eq!(result, Err(Ok(Error::NotProposed)));
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::No);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::ProposalPending)));
+    let result = s
+        .client
+        .try_propose_outcome(&disputer, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::ProposalPending)));
+}
+
+#[test]
+fn test_set_dispute_params() {
+    let s = setup();
+    let (market_id, proposer, _) = setup_proposal_market(&s);
+
+    s.client.set_dispute_params(&(BOND / 2), &60);
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+
+    let proposal = s.client.get_proposal(&market_id).unwrap();
+    assert_eq!(proposal.bond, BOND / 2);
+    assert_eq!(proposal.expires_at, s.env.ledger().timestamp() + 60);
+    assert_eq!(s.token.balance(&proposer), BOND / 2);
+}
+
+#[test]
+fn test_set_dispute_params_invalid_liveness_fails() {
+    let s = setup();
+    let result = s.client.try_set_dispute_params(&BOND, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidLiveness)));
+    let result = s.client.try_set_dispute_params(&BOND, &u64::MAX);
+    assert_eq!(result, Err(Ok(Error::InvalidLiveness)));
+    let result = s.client.try_set_dispute_params(&BOND, &(30 * 86_400 + 1));
+    assert_eq!(result, Err(Ok(Error::InvalidLiveness)));
+
+    s.client.set_dispute_params(&BOND, &(30 * 86_400));
+}
+
+// ===== Invalid Market Tests =====
+
+#[test]
+fn test_invalid_market_refunds_stakes() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &30_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::No, &20_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+
+    // The event never happened; void before the deadline
+    s.client.invalidate_market(&s.admin, &market_id);
+    let event = s.env.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        (symbol_short!("invalid"), market_id).into_val(&s.env)
+    );
+    assert_eq!(s.client.get_market(&market_id).status, MarketStatus::Invalid);
+
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 30_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 20_000_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+
+    let result = s
+        .client
+        .try_buy_shares(&bob, &market_id, &Side::Yes, &1);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+}
+
+#[test]
+fn test_invalidate_resolved_market_fails() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    let result = s.client.try_invalidate_market(&s.admin, &market_id);
+    assert_eq!(result, Err(Ok(Error::MarketAlreadyResolved)));
+}
+
+#[test]
+fn test_invalidate_disputed_market_returns_bonds() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::Yes);
+    s.client.dispute(&disputer, &market_id);
+    s.client.invalidate_market(&s.admin, &market_id);
+
+    assert_eq!(s.token.balance(&proposer), BOND);
+    assert_eq!(s.token.balance(&disputer), BOND);
+}
+
+#[test]
+fn test_invalid_categorical_market_refunds_stakes() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &30_000_000);
+
+    let market_id = create_categorical_market(&s);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(0), &10_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(2), &20_000_000);
+
+    s.client.invalidate_market(&s.admin, &market_id);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 30_000_000);
+}
+
+#[test]
+fn test_invalid_lmsr_market_refunds_cost_basis() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    let sold = s
+        .client
+        .sell_shares(&alice, &market_id, &Side::Yes, &(shares / 4), &0);
+
+    // Alice gets back what she paid less what the sale returned
+    s.client.invalidate_market(&s.admin, &market_id);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 10_000_000 - sold);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000 - sold);
+    assert_eq!(s.token.balance(&alice), 10_000_000);
+}
+
+// ===== Claim Winnings Tests =====
+
+#[test]
+fn test_winner_takes_all() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000); // 1 XLM
+    s.token_admin.mint(&bob, &20_000_000); // 2 XLM
+
+    let market_id = create_test_market(&s, 3600);
+
+    // Alice bets YES, Bob bets NO
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+
+    // Resolve as YES
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Alice wins entire pool (30M stroops = 3 XLM)
+    let payout = s.client.claim_winnings(&alice, &market_id);
+    assert_eq!(payout, 30_000_000);
+    assert_eq!(s.token.balance(&alice), 30_000_000);
+
+    // Bob gets nothing
+    let payout_bob = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(payout_bob, 0);
+    assert_eq!(s.token.balance(&bob), 0);
+}
+
+#[test]
+fn test_proportional_payout() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let charlie = Address::generate(&s.env);
+
+    s.token_admin.mint(&alice, &10_000_000); // 1 XLM
+    s.token_admin.mint(&bob, &20_000_000); // 2 XLM
+    s.token_admin.mint(&charlie, &30_000_000); // 3 XLM
+
+    let market_id = create_test_market(&s, 3600);
+
+    // Alice: 1 XLM YES, Bob: 2 XLM YES, Charlie: 3 XLM NO
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&charlie, &market_id, &Side::No, &30_000_000);
+
+    // Pool = 6 XLM. Resolve YES. Total YES = 3 XLM.
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Alice: 1/3 of 60M = 20M stroops
+    let payout_alice = s.client.claim_winnings(&alice, &market_id);
+    assert_eq!(payout_alice, 20_000_000);
+
+    // Bob: 2/3 of 60M = 40M stroops
+    let payout_bob = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(payout_bob, 40_000_000);
+
+    // Charlie: 0
+    let payout_charlie = s.client.claim_winnings(&charlie, &market_id);
+    assert_eq!(payout_charlie, 0);
+}
+
+#[test]
+fn test_no_winners_refund() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+
+    // Both bet YES
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+
+    // Resolve as NO -- no winners
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::No);
+
+    // Both get proportional refund
+    let payout_alice = s.client.claim_winnings(&alice, &market_id);
+    assert_eq!(payout_alice, 10_000_000); // 1/3 of 30M
+
+    let payout_bob = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(payout_bob, 20_000_000); // 2/3 of 30M
+}
+
+#[test]
+fn test_double_claim_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    s.client.claim_winnings(&alice, &market_id);
+    let result = s.client.try_claim_winnings(&alice, &market_id);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_claim_from_open_market_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    let result = s.client.try_claim_winnings(&alice, &market_id);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_claim_with_no_position() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp = li.timestamp + 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Bob has no position, payout should be 0
+    let payout = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(payout, 0);
+    assert_eq!(s.client.get_user_positions(&bob, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_claim_many_skips_unpayable_markets() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let (usdc, usdc_admin) = register_token(&s);
+    s.client.allow_token(&usdc.address);
+    s.token_admin.mint(&alice, &30_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    usdc_admin.mint(&alice, &10_000_000);
+
+    let won = create_test_market(&s, 3600);
+    let lost = create_test_market(&s, 3600);
+    let claimed = create_test_market(&s, 3600);
+    let won_usdc = create_market_in(&s, &usdc.address, 0);
+    let open = create_test_market(&s, 7200);
+
+    s.client.buy_shares(&alice, &won, &Side::Yes, &10_000_000);
+    s.client.buy_shares(&bob, &won, &Side::No, &10_000_000);
+    s.client.buy_shares(&alice, &lost, &Side::No, &10_000_000);
+    s.client.buy_shares(&bob, &lost, &Side::Yes, &10_000_000);
+    s.client.buy_shares(&alice, &claimed, &Side::Yes, &10_000_000);
+    s.client.buy_shares(&alice, &won_usdc, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &won, &Side::Yes);
+    s.client.resolve_market(&s.admin, &lost, &Side::Yes);
+    s.client.resolve_market(&s.admin, &claimed, &Side::Yes);
+    s.client.resolve_market(&s.admin, &won_usdc, &Side::Yes);
+    s.client.claim_winnings(&alice, &claimed);
+    assert_eq!(s.token.balance(&alice), 10_000_000);
+
+    let results = s
+        .client
+        .claim_many(&alice, &vec![&s.env, won, lost, claimed, won_usdc, open]);
+    let result = |market_id, status, payout| ClaimResult {
+        market_id,
+        status,
+        payout,
+    };
+    assert_eq!(
+        results,
+        vec![
+            &s.env,
+            result(won, ClaimStatus::Paid, 20_000_000),
+            result(lost, ClaimStatus::NoPayout, 0),
+            result(claimed, ClaimStatus::AlreadyClaimed, 0),
+            result(won_usdc, ClaimStatus::Paid, 10_000_000),
+            result(open, ClaimStatus::NotSettled, 0),
+        ]
+    );
+    assert_eq!(s.token.balance(&alice), 30_000_000);
+    assert_eq!(usdc.balance(&alice), 10_000_000);
+    assert!(s.client.get_position(&won, &alice).claimed);
+    assert!(!s.client.get_position(&lost, &alice).claimed);
+
+    // Claiming the same batch again pays nothing
+    let results = s.client.claim_many(&alice, &vec![&s.env, won, won_usdc]);
+    assert_eq!(results.get(0).unwrap().status, ClaimStatus::AlreadyClaimed);
+    assert_eq!(results.get(1).unwrap().status, ClaimStatus::AlreadyClaimed);
+    assert_eq!(s.token.balance(&alice), 30_000_000);
+}
+
+#[test]
+fn test_claim_many_skips_frozen_market() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+
+    let frozen = create_test_market(&s, 3600);
+    let won = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &frozen, &Side::Yes, &10_000_000);
+    s.client.buy_shares(&alice, &won, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &frozen, &Side::Yes);
+    s.client.resolve_market(&s.admin, &won, &Side::Yes);
+    s.client.freeze_market(&s.admin, &frozen);
+
+    let results = s.client.claim_many(&alice, &vec![&s.env, frozen, won]);
+    assert_eq!(results.get(0).unwrap().status, ClaimStatus::Paused);
+    assert_eq!(results.get(1).unwrap().status, ClaimStatus::Paid);
+    assert_eq!(s.token.balance(&alice), 10_000_000);
+    assert!(!s.client.get_position(&frozen, &alice).claimed);
+}
+
+#[test]
+fn test_claimable_matches_claim_guards() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 10_000_000);
+
+    // Nothing is reported claimable while a claim would be refused
+    s.client.freeze_market(&s.admin, &market_id);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 0);
+    s.client.unfreeze_market(&s.admin, &market_id);
+    s.client.pause(&s.admin);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 0);
+    let result = s.client.try_claim_winnings(&alice, &market_id);
+    assert_eq!(result, Err(Ok(Error::Paused)));
+
+    s.client.unpause(&s.admin);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 10_000_000);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+}
+
+#[test]
+fn test_claim_many_batch_size() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+
+    let result = s.client.try_claim_many(&alice, &vec![&s.env]);
+    assert_eq!(result, Err(Ok(Error::BatchTooLarge)));
+
+    let mut ids = vec![&s.env];
+    for id in 0..=crate::MAX_CLAIM_BATCH as u64 {
+        ids.push_back(id);
+    }
+    let result = s.client.try_claim_many(&alice, &ids);
+    assert_eq!(result, Err(Ok(Error::BatchTooLarge)));
+
+    let result = s.client.try_claim_many(&alice, &vec![&s.env, 99]);
+    assert_eq!(result, Err(Ok(Error::MarketNotFound)));
+}
+
+/// Documents the resource ceiling of `claim_many`: a full batch split across
+/// two tokens must fit the network's per-transaction limits. A full batch
+/// currently reads 13 entries and writes 21. Instructions are measured on the
+/// native build, so they only bound the wasm cost loosely.
+#[test]
+fn test_claim_many_full_batch_fits_resource_limits() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let (usdc, usdc_admin) = register_token(&s);
+    s.client.allow_token(&usdc.address);
+    s.token_admin.mint(&alice, &100_000_000);
+    usdc_admin.mint(&alice, &100_000_000);
+
+    let mut ids = vec![&s.env];
+    for i in 0..crate::MAX_CLAIM_BATCH {
+        let market_id = if i % 2 == 0 {
+            create_test_market(&s, 3600)
+        } else {
+            create_market_in(&s, &usdc.address, 0)
+        };
+        s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+        ids.push_back(market_id);
+    }
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    for market_id in ids.iter() {
+        s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    }
+
+    let results = s.client.claim_many(&alice, &ids);
+    let resources = s.env.cost_estimate().resources();
+    assert!(resources.instructions < TX_MAX_INSTRUCTIONS);
+    assert!(resources.read_entries < TX_MAX_READ_ENTRIES);
+    assert!(resources.write_entries < TX_MAX_WRITE_ENTRIES);
+
+    assert!(results.iter().all(|r| r.status == ClaimStatus::Paid));
+    assert_eq!(s.token.balance(&alice), 100_000_000);
+    assert_eq!(usdc.balance(&alice), 100_000_000);
+}
+
+// ===== Claim Window Tests =====
+
+#[test]
+fn test_sweep_after_claim_window() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let carol = Address::generate(&s.env);
+    let treasury = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    s.token_admin.mint(&carol, &10_000_001);
+
+    s.client.set_claim_window(&86_400);
+    let market_id = create_test_market(&s, 3600);
+    assert_eq!(s.client.get_market(&market_id).claim_window, 86_400);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&carol, &market_id, &Side::No, &10_000_001);
+
+    let result = s.client.try_sweep_market(&market_id, &treasury);
+    assert_eq!(result, Err(Ok(Error::MarketNotResolved)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.client.get_market(&market_id).settled_at, 3601);
+
+    // Alice claims in time; Bob never comes back
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 13_333_333);
+    assert_eq!(s.client.get_market(&market_id).claimed_total, 13_333_333);
+    let result = s.client.try_sweep_market(&market_id, &treasury);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 86_400);
+    let result = s.client.try_claim_winnings(&bob, &market_id);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowClosed)));
+    assert_eq!(s.client.get_claimable(&market_id, &bob), 0);
+    let results = s.client.claim_many(&bob, &vec![&s.env, market_id]);
+    assert_eq!(results.get_unchecked(0).status, ClaimStatus::Expired);
+
+    // Bob's unclaimed payout and the rounding dust go to the treasury
+    let swept = s.client.sweep_market(&market_id, &treasury);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(swept, 26_666_668);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("sweep"), market_id).into_val(&s.env));
+    let event: MarketSwept = data.into_val(&s.env);
+    assert_eq!(
+        event,
+        MarketSwept {
+            version: EVENT_VERSION,
+            market_id,
+            to: treasury.clone(),
+            amount: 26_666_668,
+        }
+    );
+    assert_eq!(s.token.balance(&treasury), 26_666_668);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+
+    let result = s.client.try_sweep_market(&market_id, &treasury);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+}
+
+#[test]
+fn test_claim_window_fixed_at_creation() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+    assert_eq!(s.client.get_claim_window(), 31_536_000);
+
+    s.client.set_claim_window(&0);
+    let forever = create_test_market(&s, 3600);
+    s.client.set_claim_window(&86_400);
+    let windowed = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &forever, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&alice, &windowed, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &forever, &Side::Yes);
+    s.client.resolve_market(&s.admin, &windowed, &Side::Yes);
+
+    // Shortening the default later does not touch existing markets
+    s.client.set_claim_window(&1);
+    s.env.ledger().with_mut(|li| li.timestamp += 86_400);
+    assert_eq!(s.client.claim_winnings(&alice, &forever), 10_000_000);
+    let result = s.client.try_claim_winnings(&alice, &windowed);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowClosed)));
+
+    let result = s.client.try_sweep_market(&forever, &s.admin);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+    assert_eq!(s.client.sweep_market(&windowed, &s.admin), 10_000_000);
+}
+
+#[test]
+fn test_claim_window_max_never_closes() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.client.set_claim_window(&u64::MAX);
+    let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    s.env.ledger().with_mut(|li| li.timestamp += 10 * 31_536_000);
+
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 10_000_000);
+    assert_eq!(s.client.get_user_positions(&alice, &0, &10).len(), 1);
+    let result = s.client.try_sweep_market(&market_id, &s.admin);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowOpen)));
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+}
+
+#[test]
+fn test_sweep_takes_unwithdrawn_liquidity() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    s.client.set_claim_window(&86_400);
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    let payout = s.client.claim_winnings(&alice, &market_id);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 86_400);
+    let result = s.client.try_remove_liquidity(&lp, &market_id, &100_000_000);
+    assert_eq!(result, Err(Ok(Error::ClaimWindowClosed)));
+
+    let swept = s.client.sweep_market(&market_id, &s.admin);
+    assert_eq!(swept, 110_000_000 - payout);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+}
+
+// ===== Solvency Tests =====
+
+/// While tokens only move through the contract's own calls, what its
+/// markets, bonds and fees are owed adds up to the balance exactly.
+fn assert_solvent(s: &TestSetup) -> i128 {
+    let report = s.client.get_solvency();
+    let balance = s.token.balance(&s.client.address);
+    assert_eq!(
+        report,
+        vec![
+            &s.env,
+            TokenSolvency {
+                token: s.token.address.clone(),
+                liabilities: balance,
+                balance,
+            }
+        ]
+    );
+    balance
+}
+
+#[test]
+fn test_solvency_tracks_every_transfer() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let (proposer, disputer) = (Address::generate(&s.env), Address::generate(&s.env));
+    for user in [&s.admin, &alice, &bob, &proposer, &disputer] {
+        s.token_admin.mint(user, &200_000_000);
+    }
+    assert_eq!(s.client.get_solvency(), vec![&s.env]);
+    s.client.set_protocol_fee(&s.admin, &100);
+
+    let parimutuel = create_market_with_fee(&s, &bob, 200);
+    let lmsr = create_lmsr_market(&s, 100_000_000);
+    let cpmm = create_cpmm_market(&s);
+    s.client.add_liquidity(&bob, &cpmm, &100_000_000);
+    s.client
+        .buy_shares(&alice, &parimutuel, &Side::Yes, &30_000_000);
+    s.client
+        .buy_shares(&bob, &parimutuel, &Side::No, &10_000_000);
+    s.client.buy_shares(&alice, &lmsr, &Side::Yes, &20_000_000);
+    s.client.buy_shares(&alice, &cpmm, &Side::No, &20_000_000);
+    s.client
+        .sell_shares(&bob, &parimutuel, &Side::No, &1_000_000, &0);
+    assert_solvent(&s);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client
+        .propose_outcome(&proposer, &parimutuel, &Side::Yes);
+    s.client.dispute(&disputer, &parimutuel);
+    assert_solvent(&s);
+    s.client.resolve_market(&s.admin, &parimutuel, &Side::Yes);
+    s.client.resolve_market(&s.admin, &lmsr, &Side::Yes);
+    s.client.invalidate_market(&s.admin, &cpmm);
+
+    s.client.claim_winnings(&alice, &parimutuel);
+    s.client.claim_many(&alice, &vec![&s.env, lmsr, cpmm]);
+    s.client.claim_winnings(&bob, &cpmm);
+    s.client
+        .withdraw_protocol_fees(&s.admin, &s.token.address, &s.admin);
+    s.client.withdraw_creator_fees(&bob, &s.token.address);
+
+    // Only the LMSR subsidy's unspent part and rounding dust remain
+    let left = assert_solvent(&s);
+    assert!(left < 100_000_000);
+}
+
+#[test]
+fn test_solvency_reports_what_is_owed() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let donor = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &30_000_000);
+    s.token_admin.mint(&donor, &5_000_000);
+    let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &30_000_000);
+    assert_solvent(&s);
+
+    // Tokens sent straight to the contract are not owed to anyone
+    s.token.transfer(&donor, &s.client.address, &5_000_000);
+    let report = s.client.get_solvency().get_unchecked(0);
+    assert_eq!(report.liabilities, 30_000_000);
+    assert_eq!(report.balance, 35_000_000);
+
+    // Tokens leaving outside the contract's books show up as a shortfall
+    let thief = Address::generate(&s.env);
+    s.token.transfer(&s.client.address, &thief, &15_000_000);
+    let report = s.client.get_solvency().get_unchecked(0);
+    assert_eq!(report.liabilities, 30_000_000);
+    assert_eq!(report.balance, 20_000_000);
+}
+
+#[test]
+fn test_claims_book_paid_out_totals() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let carol = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    s.token_admin.mint(&carol, &10_000_001);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&carol, &market_id, &Side::No, &10_000_001);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    s.client.claim_winnings(&alice, &market_id);
+    s.client.claim_winnings(&bob, &market_id);
+    s.client.claim_winnings(&carol, &market_id);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_paid_out, 40_000_000);
+    assert_eq!(market.claimed_total, 40_000_000);
+    assert_eq!(market.claimed_winning_shares, 30_000_000);
+    assert_eq!(market.pool_balance - market.total_paid_out, 1);
+    assert_eq!(assert_solvent(&s), 1);
+}
+
+#[test]
+fn test_claim_beyond_pool_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Corrupt the books so the pool looks nearly drained
+    s.env.as_contract(&s.client.address, || {
+        let key = DataKey::Market(market_id);
+        let mut market: Market = s.env.storage().persistent().get(&key).unwrap();
+        market.total_paid_out = market.pool_balance - 1;
+        s.env.storage().persistent().set(&key, &market);
+    });
+
+    let result = s.client.try_claim_winnings(&alice, &market_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientPool)));
+    let result = s.client.try_claim_many(&alice, &vec![&s.env, market_id]);
+    assert_eq!(result, Err(Ok(Error::InsufficientPool)));
+    assert!(!s.client.get_position(&market_id, &alice).claimed);
+    assert_eq!(s.token.balance(&alice), 0);
+}
+
+// ===== Overflow Tests =====
+
+#[test]
+fn test_large_pool_settles() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let carol = Address::generate(&s.env);
+    let stake = i128::MAX / 4;
+    s.token_admin.mint(&alice, &stake);
+    s.token_admin.mint(&bob, &stake);
+    s.token_admin.mint(&carol, &stake);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &stake);
+    s.client.buy_shares(&carol, &market_id, &Side::Yes, &stake);
+    s.client.buy_shares(&bob, &market_id, &Side::No, &stake);
+
+    // Every pro-rata product below is far past i128::MAX
+    assert_eq!(s.client.get_odds(&market_id), vec![&s.env, 6_666, 3_334]);
+    let quote = s.client.quote_buy(&market_id, &Side::Yes, &stake);
+    assert_eq!(quote, 4 * stake / 3);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    let payout = 3 * stake / 2;
+    assert_eq!(s.client.get_claimable(&market_id, &alice), payout);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), payout);
+    assert_eq!(s.client.claim_winnings(&carol, &market_id), payout);
+    assert_eq!(s.token.balance(&alice), payout);
+    assert_eq!(assert_solvent(&s), 3 * stake - 2 * payout);
+}
+
+#[test]
+fn test_wide_scalar_range_settles() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+    s.token_admin.mint(&bob, &10_000_000);
+
+    let half = i128::MAX / 2;
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let market_id = s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Scalar(ScalarRange {
+            low: -half,
+            high: half,
+        }),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+
+    // Dead centre: LONG and SHORT split the pool evenly
+    s.client.resolve_scalar(&s.admin, &market_id, &0);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 15_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 15_000_000);
+}
+
+#[test]
+fn test_overflowing_totals_fail() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::No, &10_000_000);
+
+    // Corrupt the books so the YES total sits at the limit
+    s.env.as_contract(&s.client.address, || {
+        let key = DataKey::Market(market_id);
+        let mut market: Market = s.env.storage().persistent().get(&key).unwrap();
+        market.total_yes = i128::MAX;
+        s.env.storage().persistent().set(&key, &market);
+    });
+
+    let result = s.client.try_get_odds(&market_id);
+    assert_eq!(result, Err(Ok(Error::Overflow)));
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::Overflow)));
+    assert_eq!(s.token.balance(&alice), 10_000_000);
+    assert_eq!(s.client.get_position(&market_id, &alice).yes_shares, 0);
+}
+
+#[test]
+fn test_lmsr_subsidy_near_limit_fails() {
+    let s = setup();
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let create = |b: i128| {
+        s.client.try_create_market(
+            &s.admin,
+            &String::from_str(&s.env, "Will it rain tomorrow?"),
+            &deadline,
+            &MarketKind::Lmsr(b),
+            &None,
+            &None,
+            &0,
+            &None,
+            &None,
+        )
+    };
+
+    // b * ln(2) fits but rounding it up to whole stroops does not
+    let ln2 = 693_147_180_559_945_309;
+    assert_eq!(create(i128::MAX / ln2), Err(Ok(Error::Overflow)));
+    assert_eq!(create(i128::MAX / ln2 + 1), Err(Ok(Error::InvalidLiquidity)));
+    assert_eq!(create(i128::MAX), Err(Ok(Error::InvalidLiquidity)));
+}
+
+// ===== LMSR Market Tests =====
+
+#[test]
+fn test_create_lmsr_market_takes_subsidy() {
+    let s = setup();
+    s.token_admin.mint(&s.admin, &100_000_000);
+
+    // b = 10 XLM -> subsidy = ceil(b * ln 2)
+    let market_id = create_lmsr_market(&s, 100_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.kind, MarketKind::Lmsr(100_000_000));
+    assert_eq!(market.pool_balance, 69_314_719);
+    assert_eq!(s.token.balance(&s.admin), 100_000_000 - 69_314_719);
+}
+
+#[test]
+fn test_create_lmsr_market_invalid_liquidity_fails() {
+    let s = setup();
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Lmsr(0),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidLiquidity)));
+}
+
+#[test]
+fn test_lmsr_price_moves_with_demand() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &20_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+
+    // At 50/50 odds a small buy gets close to 2 shares per stroop
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let first = s.client.get_position(&market_id, &alice).yes_shares;
+    assert!(first > 19_000_000 && first < 20_000_000);
+
+    // The same spend now buys fewer YES shares
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let second = s.client.get_position(&market_id, &alice).yes_shares - first;
+    assert!(second < first);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, first + second);
+    assert_eq!(market.pool_balance, 69_314_719 + 20_000_000);
+}
+
+#[test]
+fn test_lmsr_payout_is_one_per_share() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &50_000_000);
+    s.token_admin.mint(&bob, &30_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &50_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &30_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    let payout = s.client.claim_winnings(&alice, &market_id);
+    assert_eq!(payout, shares);
+
+    // Pool (subsidy + stakes) covers every winning share
+    let market = s.client.get_market(&market_id);
+    assert!(market.pool_balance >= market.total_yes);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 0);
+}
+
+#[test]
+fn test_lmsr_creator_withdraws_surplus() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &50_000_000);
+    s.token_admin.mint(&bob, &30_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &50_000_000);
+    s.client.buy_shares(&bob, &market_id, &Side::No, &30_000_000);
+    let result = s.client.try_withdraw_surplus(&market_id);
+    assert_eq!(result, Err(Ok(Error::MarketNotResolved)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    // Everything beyond the winning shares goes back to the creator
+    let market = s.client.get_market(&market_id);
+    let surplus = s.client.withdraw_surplus(&market_id);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(surplus, market.pool_balance - market.total_yes);
+    assert!(surplus > 0);
+    assert_eq!(s.token.balance(&s.admin), 100_000_000 - 69_314_719 + surplus);
+    let result = s.client.try_withdraw_surplus(&market_id);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+
+    // Winners are still paid in full, leaving nothing behind
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), shares);
+    assert_eq!(assert_solvent(&s), 0);
+}
+
+#[test]
+fn test_invalid_lmsr_surplus_waits_for_refunds() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    // The creator is also the resolver, and voids their own market
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client.invalidate_market(&s.admin, &market_id);
+
+    let result = s.client.try_withdraw_surplus(&market_id);
+    assert_eq!(result, Err(Ok(Error::RefundsPending)));
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+
+    // Only the subsidy is left for the creator
+    assert_eq!(s.client.withdraw_surplus(&market_id), 69_314_719);
+    assert_eq!(s.token.balance(&s.admin), 100_000_000);
+    assert_eq!(assert_solvent(&s), 0);
+}
+
+#[test]
+fn test_invalid_lmsr_surplus_after_claim_window() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_lmsr_market(&s, 100_000_000);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client.invalidate_market(&s.admin, &market_id);
+
+    // Refunds nobody claimed in time are no longer owed
+    let window = s.client.get_market(&market_id).claim_window;
+    s.env.ledger().with_mut(|li| li.timestamp += window);
+    let surplus = s.client.withdraw_surplus(&market_id);
+    assert_eq!(surplus, 69_314_719 + 10_000_000);
+    assert_eq!(assert_solvent(&s), 0);
+}
+
+#[test]
+fn test_withdraw_surplus_non_lmsr_fails() {
+    let s = setup();
+    let market_id = create_test_market(&s, 3600);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    let result = s.client.try_withdraw_surplus(&market_id);
+    assert_eq!(result, Err(Ok(Error::InvalidMarketKind)));
+}
+
+#[test]
+fn test_lmsr_one_sided_market_stays_solvent() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &10_000_000);
+    s.token_admin.mint(&alice, &1_000_000_000);
+
+    // Buying far past the liquidity parameter drives the YES price toward 1
+    let market_id = create_lmsr_market(&s, 10_000_000);
+    for _ in 0..10 {
+        s.client
+            .buy_shares(&alice, &market_id, &Side::Yes, &100_000_000);
+    }
+
+    let market = s.client.get_market(&market_id);
+    assert!(market.total_yes > 1_000_000_000);
+    assert!(market.pool_balance >= market.total_yes);
+}
+
+// ===== CPMM Market Tests =====
+
+fn create_cpmm_market(s: &TestSetup) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Cpmm,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    )
+}
+
+#[test]
+fn test_cpmm_add_liquidity_seeds_pool() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    let minted = s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    assert_eq!(minted, 100_000_000);
+
+    let pool = s.client.get_pool(&market_id);
+    assert_eq!(pool.yes_reserve, 100_000_000);
+    assert_eq!(pool.no_reserve, 100_000_000);
+    assert_eq!(pool.lp_supply, 100_000_000);
+    assert_eq!(s.client.get_lp_shares(&market_id, &lp), 100_000_000);
+    assert_eq!(s.client.get_market(&market_id).pool_balance, 100_000_000);
+}
+
+#[test]
+fn test_cpmm_buy_without_liquidity_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::NoLiquidity)));
+}
+
+#[test]
+fn test_add_liquidity_to_parimutuel_fails() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &10_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    let result = s.client.try_add_liquidity(&lp, &market_id, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::InvalidMarketKind)));
+}
+
+#[test]
+fn test_add_liquidity_after_trading_closes_fails() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &20_000_000);
+
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let market_id = s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Will it rain tomorrow?"),
+        &deadline,
+        &MarketKind::Cpmm,
+        &None,
+        &None,
+        &0,
+        &None,
+        &Some(deadline - 600),
+    );
+    s.client.add_liquidity(&lp, &market_id, &10_000_000);
+
+    // Liquidity can still be withdrawn during the lock period
+    s.env.ledger().with_mut(|li| li.timestamp = deadline - 600);
+    let result = s.client.try_add_liquidity(&lp, &market_id, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+    assert_eq!(s.client.remove_liquidity(&lp, &market_id, &10_000_000), 10_000_000);
+}
+
+#[test]
+fn test_cpmm_buy_preserves_product() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &50_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+
+    // 100 * 100 = (100 + 50 - out) * 150  ->  out = 150 - 66.67 = 83.33 (in XLM)
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &50_000_000);
+    let pos = s.client.get_position(&market_id, &alice);
+    assert_eq!(pos.yes_shares, 83_333_333);
+
+    let pool = s.client.get_pool(&market_id);
+    assert_eq!(pool.yes_reserve, 66_666_667);
+    assert_eq!(pool.no_reserve, 150_000_000);
+    assert!(pool.yes_reserve * pool.no_reserve >= 100_000_000 * 100_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 83_333_333);
+    assert_eq!(market.pool_balance, 150_000_000);
+}
+
+#[test]
+fn test_cpmm_sell_shares() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &50_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &50_000_000);
+
+    // Selling everything straight back returns the stake, minus rounding
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    let out = s
+        .client
+        .sell_shares(&alice, &market_id, &Side::Yes, &shares, &49_999_990);
+    assert!((49_999_990..=50_000_000).contains(&out));
+    assert_eq!(s.token.balance(&alice), out);
+    assert_eq!(s.client.get_position(&market_id, &alice).yes_shares, 0);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.total_yes, 0);
+    assert_eq!(market.pool_balance, 150_000_000 - out);
+}
+
+#[test]
+fn test_cpmm_sell_slippage_and_balance_checks() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &(shares + 1), &0);
+    assert_eq!(result, Err(Ok(Error::InsufficientShares)));
+
+    let result = s
+        .client
+        .try_sell_shares(&alice, &market_id, &Side::Yes, &shares, &10_000_001);
+    assert_eq!(result, Err(Ok(Error::SlippageExceeded)));
+}
+
+#[test]
+fn test_cpmm_add_liquidity_keeps_price() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let lp2 = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&lp2, &60_000_000);
+    s.token_admin.mint(&alice, &50_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &50_000_000);
+
+    // Reserves are 66.67 YES / 150 NO; a 60 XLM deposit adds 40% to each
+    let minted = s.client.add_liquidity(&lp2, &market_id, &60_000_000);
+    assert_eq!(minted, 40_000_000);
+
+    let pool = s.client.get_pool(&market_id);
+    assert_eq!(pool.no_reserve, 210_000_000);
+    assert_eq!(pool.yes_reserve, 66_666_667 + 26_666_666);
+
+    // The YES not absorbed by the pool is credited to the provider
+    let pos = s.client.get_position(&market_id, &lp2);
+    assert_eq!(pos.yes_shares, 60_000_000 - 26_666_666);
+    assert_eq!(pos.no_shares, 0);
+}
+
+#[test]
+fn test_cpmm_full_flow_settles() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &30_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &30_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+
+    let alice_shares = s.client.get_position(&market_id, &alice).yes_shares;
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), alice_shares);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 0);
+
+    // The LP takes the winning reserves, booked as paid out of the pool
+    let pool = s.client.get_pool(&market_id);
+    let lp_out = s.client.remove_liquidity(&lp, &market_id, &100_000_000);
+    assert_eq!(lp_out, pool.yes_reserve);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.pool_balance, 150_000_000);
+    assert_eq!(market.total_paid_out, 150_000_000);
+
+    // Every stroop paid in is paid back out
+    assert_eq!(alice_shares + lp_out, 150_000_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+}
+
+#[test]
+fn test_invalid_cpmm_market_refunds_deposits() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &30_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &30_000_000);
+    s.client.invalidate_market(&s.admin, &market_id);
+
+    // Liquidity is refunded along with trades, not out of the reserves
+    let result = s.client.try_remove_liquidity(&lp, &market_id, &100_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 30_000_000);
+    assert_eq!(s.client.claim_winnings(&lp, &market_id), 100_000_000);
+    assert_eq!(s.client.claim_winnings(&lp, &market_id), 0);
+    assert_eq!(assert_solvent(&s), 0);
+}
+
+#[test]
+fn test_invalid_cpmm_market_scales_refunds_to_pool() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &50_000_000);
+
+    // Alice sells into Bob's buying at a profit, which refunds can't cover
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &50_000_000);
+    let shares = s.client.get_position(&market_id, &alice).yes_shares;
+    let sold = s
+        .client
+        .sell_shares(&alice, &market_id, &Side::Yes, &shares, &0);
+    assert!(sold > 10_000_000);
+    s.client.invalidate_market(&s.admin, &market_id);
+
+    let pool = s.client.get_market(&market_id).pool_balance;
+    let owed = 150_000_000;
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 0);
+    let bob_refund = s.client.claim_winnings(&bob, &market_id);
+    assert_eq!(bob_refund, 50_000_000 * pool / owed);
+
+    // Each refund keeps the same ratio, so the last one takes the dust
+    assert_eq!(s.client.claim_winnings(&lp, &market_id), pool - bob_refund);
+    assert_eq!(assert_solvent(&s), 0);
+}
+
+// ===== Categorical Market Tests =====
+
+fn create_categorical_market(s: &TestSetup) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let labels = vec![
+        &s.env,
+        String::from_str(&s.env, "Brazil"),
+        String::from_str(&s.env, "Germany"),
+        String::from_str(&s.env, "Japan"),
+    ];
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Who wins the group?"),
+        &deadline,
+        &MarketKind::Categorical(labels),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    )
+}
+
+#[test]
+fn test_create_categorical_market() {
+    let s = setup();
+    let market_id = create_categorical_market(&s);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.outcome_totals, vec![&s.env, 0, 0, 0]);
+    match market.kind {
+        MarketKind::Categorical(labels) => assert_eq!(labels.len(), 3),
+        _ => panic!("expected categorical market"),
+    }
+}
+
+#[test]
+fn test_create_categorical_market_outcome_count() {
+    let s = setup();
+    let deadline = s.env.ledger().timestamp() + 3600;
+
+    let two = vec![
+        &s.env,
+        String::from_str(&s.env, "A"),
+        String::from_str(&s.env, "B"),
+    ];
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Categorical(two),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidOutcomeCount)));
+
+    let mut seventeen = vec![&s.env];
+    for _ in 0..17 {
+        seventeen.push_back(String::from_str(&s.env, "X"));
+    }
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Categorical(seventeen),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidOutcomeCount)));
+}
+
+#[test]
+fn test_categorical_buy_outcome_shares() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &30_000_000);
+
+    let market_id = create_categorical_market(&s);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(2), &10_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(0), &20_000_000);
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.outcome_totals, vec![&s.env, 20_000_000, 0, 10_000_000]);
+    assert_eq!(market.pool_balance, 30_000_000);
+
+    let pos = s.client.get_position(&market_id, &alice);
+    assert_eq!(pos.outcome_shares, vec![&s.env, 20_000_000, 0, 10_000_000]);
+    assert_eq!(pos.yes_shares, 0);
+}
+
+#[test]
+fn test_categorical_invalid_side_fails() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_categorical_market(&s);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::InvalidOutcome)));
+    let result = s
+        .client
+        .try_buy_shares(&alice, &market_id, &Side::Outcome(3), &10_000_000);
+    assert_eq!(result, Err(Ok(Error::InvalidOutcome)));
+
+    // Binary markets don't take outcome indices
+    let binary_id = create_test_market(&s, 3600);
+    let result = s
+        .client
+        .try_buy_shares(&alice, &binary_id, &Side::Outcome(0), &10_000_000);
+    assert_eq!(result, Err(Ok(Error::InvalidOutcome)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Outcome(7));
+    assert_eq!(result, Err(Ok(Error::InvalidOutcome)));
+}
+
+#[test]
+fn test_categorical_proportional_payout() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let charlie = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    s.token_admin.mint(&charlie, &30_000_000);
+
+    let market_id = create_categorical_market(&s);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(1), &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Outcome(1), &20_000_000);
+    s.client
+        .buy_shares(&charlie, &market_id, &Side::Outcome(2), &30_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Outcome(1));
+
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Outcome(1)));
+
+    // Pool = 60M, winning outcome total = 30M
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 20_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 40_000_000);
+    assert_eq!(s.client.claim_winnings(&charlie, &market_id), 0);
+}
+
+#[test]
+fn test_categorical_no_winners_refund() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_categorical_market(&s);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Outcome(0), &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Outcome(1), &20_000_000);
+
+    // Nobody backed the winning outcome
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Outcome(2));
+
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 20_000_000);
+}
+
+// ===== Scalar Market Tests =====
+
+/// "XLM price on date X", in cents, bounded to [$0.10, $0.50].
+fn create_scalar_market(s: &TestSetup) -> u64 {
+    let deadline = s.env.ledger().timestamp() + 3600;
+    s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "XLM price on Friday?"),
+        &deadline,
+        &MarketKind::Scalar(ScalarRange { low: 10, high: 50 }),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    )
+}
+
+/// Alice and Bob go LONG with 1 and 2 XLM, Charlie goes SHORT with 3 XLM.
+fn fund_scalar_market(s: &TestSetup, market_id: u64) -> (Address, Address, Address) {
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let charlie = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+    s.token_admin.mint(&charlie, &30_000_000);
+
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::Yes, &20_000_000);
+    s.client
+        .buy_shares(&charlie, &market_id, &Side::No, &30_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    (alice, bob, charlie)
+}
+
+#[test]
+fn test_create_scalar_market_invalid_range_fails() {
+    let s = setup();
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Scalar(ScalarRange { low: 50, high: 50 }),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidRange)));
+
+    // A range whose width does not fit in an i128 could never be settled
+    let wide = ScalarRange {
+        low: i128::MIN,
+        high: i128::MAX,
+    };
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &deadline,
+        &MarketKind::Scalar(wide),
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidRange)));
+}
+
+#[test]
+fn test_scalar_linear_payout() {
+    let s = setup();
+    let market_id = create_scalar_market(&s);
+    let (alice, bob, charlie) = fund_scalar_market(&s, market_id);
+
+    // 20 is a quarter of the way from 10 to 50: LONG gets 1/4 of the 6 XLM pool
+    s.client.resolve_scalar(&s.admin, &market_id, &20);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::ResolvedScalar(20));
+
+    // LONG pool = 15M split 1:2, SHORT pool = 45M
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 5_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 10_000_000);
+    assert_eq!(s.client.claim_winnings(&charlie, &market_id), 45_000_000);
+}
+
+#[test]
+fn test_scalar_payout_clamped_at_bounds() {
+    let s = setup();
+    let market_id = create_scalar_market(&s);
+    let (alice, bob, charlie) = fund_scalar_market(&s, market_id);
+
+    // Above the range: LONG takes the whole pool
+    s.client.resolve_scalar(&s.admin, &market_id, &1_000);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 20_000_000);
+    assert_eq!(s.client.claim_winnings(&bob, &market_id), 40_000_000);
+    assert_eq!(s.client.claim_winnings(&charlie, &market_id), 0);
+
+    let s = setup();
+    let market_id = create_scalar_market(&s);
+    let (alice, _, charlie) = fund_scalar_market(&s, market_id);
+
+    // Below the range: SHORT takes the whole pool
+    s.client.resolve_scalar(&s.admin, &market_id, &-5);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 0);
+    assert_eq!(s.client.claim_winnings(&charlie, &market_id), 60_000_000);
+}
+
+#[test]
+fn test_scalar_one_sided_refund() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_scalar_market(&s);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+
+    // Nobody went SHORT, so LONG gets the pool back even at the low bound
+    s.client.resolve_scalar(&s.admin, &market_id, &10);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+}
+
+#[test]
+fn test_scalar_resolution_entry_points() {
+    let s = setup();
+    let market_id = create_scalar_market(&s);
+    let binary_id = create_test_market(&s, 3600);
+
+    let result = s.client.try_resolve_scalar(&s.admin, &market_id, &20);
+    assert_eq!(result, Err(Ok(Error::DeadlineNotReached)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result = s.client.try_resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(result, Err(Ok(Error::InvalidMarketKind)));
+    let result = s.client.try_resolve_scalar(&s.admin, &binary_id, &20);
+    assert_eq!(result, Err(Ok(Error::InvalidMarketKind)));
+
+    s.client.resolve_scalar(&s.admin, &market_id, &20);
+    let result = s.client.try_resolve_scalar(&s.admin, &market_id, &30);
+    assert_eq!(result, Err(Ok(Error::MarketAlreadyResolved)));
+}
+
+// ===== Oracle Resolution Tests =====
+
+fn create_oracle_market(s: &TestSetup, kind: MarketKind) -> (u64, MockOracleClient<'static>) {
+    let oracle_id = s.env.register(MockOracle, ());
+    let oracle = MockOracleClient::new(&s.env, &oracle_id);
+    let spec = OracleSpec {
+        oracle: oracle_id,
+        asset: symbol_short!("XLM"),
+        comparison: Comparison::Gt,
+        threshold: 5_000_000, // $0.50 at 7 decimals
+    };
+    let deadline = s.env.ledger().timestamp() + 3600;
+    let market_id = s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "XLM above $0.50?"),
+        &deadline,
+        &kind,
+        &Some(spec),
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    (market_id, oracle)
+}
+
+fn xlm() -> Asset {
+    Asset::Other(symbol_short!("XLM"))
+}
+
+#[test]
+fn test_resolve_from_oracle_yes() {
+    let s = setup();
+    let (market_id, oracle) = create_oracle_market(&s, MarketKind::Parimutuel);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    oracle.set_price(&xlm(), &5_100_000, &3600);
+
+    assert_eq!(
+        s.client.get_oracle(&market_id).unwrap().threshold,
+        5_000_000
+    );
+
+    // Anyone can trigger settlement
+    s.client.resolve_from_oracle(&market_id);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+}
+
+#[test]
+fn test_resolve_from_oracle_no() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    let (market_id, oracle) = create_oracle_market(&s, MarketKind::Parimutuel);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::No, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    oracle.set_price(&xlm(), &5_000_000, &3600);
+
+    s.client.resolve_from_oracle(&market_id);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::No));
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), 10_000_000);
+}
+
+#[test]
+fn test_resolve_from_oracle_scalar() {
+    let s = setup();
+    let kind = MarketKind::Scalar(ScalarRange {
+        low: 1_000_000,
+        high: 9_000_000,
+    });
+    let (market_id, oracle) = create_oracle_market(&s, kind);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    oracle.set_price(&xlm(), &4_200_000, &3600);
+
+    s.client.resolve_from_oracle(&market_id);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::ResolvedScalar(4_200_000));
+}
+
+#[test]
+fn test_resolve_from_oracle_failures() {
+    let s = setup();
+    let (market_id, oracle) = create_oracle_market(&s, MarketKind::Parimutuel);
+
+    let result = s.client.try_resolve_from_oracle(&market_id);
+    assert_eq!(result, Err(Ok(Error::DeadlineNotReached)));
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    let result = s.client.try_resolve_from_oracle(&market_id);
+    assert_eq!(result, Err(Ok(Error::OraclePriceUnavailable)));
+
+    // Prices recorded before the deadline don't count
+    oracle.set_price(&xlm(), &9_000_000, &3300);
+    let result = s.client.try_resolve_from_oracle(&market_id);
+    assert_eq!(result, Err(Ok(Error::OraclePriceUnavailable)));
+
+    let manual_id = create_test_market(&s, 3600);
+    let result = s.client.try_resolve_from_oracle(&manual_id);
+    assert_eq!(result, Err(Ok(Error::NoOracle)));
+}
+
+#[test]
+fn test_resolve_from_oracle_uses_price_at_deadline() {
+    let s = setup();
+    let (market_id, oracle) = create_oracle_market(&s, MarketKind::Parimutuel);
+    oracle.set_price(&xlm(), &5_100_000, &3600);
+    s.env.ledger().with_mut(|li| li.timestamp += 7200);
+    oracle.set_price(&xlm(), &4_000_000, &7200);
+
+    // The latest price says No, but the record at the deadline settles it
+    s.client.resolve_from_oracle(&market_id);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+}
+
+#[test]
+fn test_resolve_from_oracle_rounds_deadline_up() {
+    let s = setup();
+    let (market_id, oracle) = create_oracle_market(&s, MarketKind::Parimutuel);
+    let deadline = s.env.ledger().timestamp() + 3601;
+    let market_id_unaligned = s.client.create_market(
+        &s.admin,
+        &String::from_str(&s.env, "XLM above $0.50?"),
+        &deadline,
+        &MarketKind::Parimutuel,
+        &s.client.get_oracle(&market_id),
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    oracle.set_price(&xlm(), &4_000_000, &3600);
+    oracle.set_price(&xlm(), &5_100_000, &3900);
+    s.env.ledger().with_mut(|li| li.timestamp += 4000);
+
+    s.client.resolve_from_oracle(&market_id_unaligned);
+    let market = s.client.get_market(&market_id_unaligned);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::Yes));
+    s.client.resolve_from_oracle(&market_id);
+    let market = s.client.get_market(&market_id);
+    assert_eq!(market.status, MarketStatus::Resolved(Side::No));
+}
+
+#[test]
+fn test_categorical_market_with_oracle_fails() {
+    let s = setup();
+    let spec = OracleSpec {
+        oracle: Address::generate(&s.env),
+        asset: symbol_short!("XLM"),
+        comparison: Comparison::Gt,
+        threshold: 0,
+    };
+    let labels = vec![
+        &s.env,
+        String::from_str(&s.env, "A"),
+        String::from_str(&s.env, "B"),
+        String::from_str(&s.env, "C"),
+    ];
+    let result = s.client.try_create_market(
+        &s.admin,
+        &String::from_str(&s.env, "Test?"),
+        &(s.env.ledger().timestamp() + 3600),
+        &MarketKind::Categorical(labels),
+        &Some(spec),
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidMarketKind)));
+}
+
+// ===== Event Tests =====
+
+/// Topics and data of the last event, which must come from the contract.
+fn last_event(s: &TestSetup) -> (soroban_sdk::Vec<Val>, Val) {
+    let (contract, topics, data) = s.env.events().all().last().unwrap();
+    assert_eq!(contract, s.client.address);
+    (topics, data)
+}
+
+#[test]
+fn test_market_and_trade_events() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+
+    let market_id = create_market_with_fee(&s, &s.admin, 100);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("create"), market_id).into_val(&s.env));
+    let created: MarketCreated = data.into_val(&s.env);
+    assert_eq!(
+        created,
+        MarketCreated {
+            version: EVENT_VERSION,
+            market_id,
+            creator: s.admin.clone(),
+            resolver: None,
+            question: String::from_str(&s.env, "Will it rain tomorrow?"),
+            deadline: 3600,
+            trading_closes_at: 3600,
+            kind: MarketKind::Parimutuel,
+            token: s.token.address.clone(),
+            creator_fee_bps: 100,
+            pool_balance: 0,
+        }
+    );
+
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("buy"), market_id, alice.clone()).into_val(&s.env)
+    );
+    let trade: Trade = data.into_val(&s.env);
+    assert_eq!(
+        trade,
+        Trade {
+            version: EVENT_VERSION,
+            market_id,
+            trader: alice.clone(),
+            side: Side::Yes,
+            amount: 10_000_000,
+            shares: 9_900_000,
+            total_yes: 9_900_000,
+            total_no: 0,
+            outcome_totals: vec![&s.env],
+            pool_balance: 9_900_000,
+        }
+    );
+
+    s.client
+        .sell_shares(&alice, &market_id, &Side::Yes, &4_000_000, &0);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("sell"), market_id, alice.clone()).into_val(&s.env)
+    );
+    let trade: Trade = data.into_val(&s.env);
+    assert_eq!(trade.amount, 4_000_000);
+    assert_eq!(trade.shares, 4_000_000);
+    assert_eq!(trade.total_yes, 5_900_000);
+    assert_eq!(trade.pool_balance, 5_900_000);
+}
+
+#[test]
+fn test_settlement_and_claim_events() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("resolve"), market_id).into_val(&s.env));
+    let settled: MarketSettled = data.into_val(&s.env);
+    assert_eq!(
+        settled,
+        MarketSettled {
+            version: EVENT_VERSION,
+            market_id,
+            status: MarketStatus::Resolved(Side::Yes),
+            total_yes: 10_000_000,
+            total_no: 20_000_000,
+            outcome_totals: vec![&s.env],
+            pool_balance: 30_000_000,
+        }
+    );
+
+    s.client.claim_winnings(&alice, &market_id);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("claim"), market_id, alice.clone()).into_val(&s.env)
+    );
+    let claimed: Claimed = data.into_val(&s.env);
+    assert_eq!(
+        claimed,
+        Claimed {
+            version: EVENT_VERSION,
+            market_id,
+            user: alice,
+            payout: 30_000_000,
+        }
+    );
+}
+
+#[test]
+fn test_liquidity_events() {
+    let s = setup();
+    let lp = Address::generate(&s.env);
+    s.token_admin.mint(&lp, &100_000_000);
+
+    let market_id = create_cpmm_market(&s);
+    s.client.add_liquidity(&lp, &market_id, &100_000_000);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("add_liq"), market_id, lp.clone()).into_val(&s.env)
+    );
+    let added: LiquidityChanged = data.into_val(&s.env);
+    assert_eq!(
+        added,
+        LiquidityChanged {
+            version: EVENT_VERSION,
+            market_id,
+            provider: lp.clone(),
+            amount: 100_000_000,
+            lp_shares: 100_000_000,
+            yes_reserve: 100_000_000,
+            no_reserve: 100_000_000,
+            lp_supply: 100_000_000,
+            total_yes: 0,
+            total_no: 0,
+            pool_balance: 100_000_000,
+        }
+    );
+}
+
+#[test]
+fn test_proposal_events() {
+    let s = setup();
+    let (market_id, proposer, disputer) = setup_proposal_market(&s);
+
+    s.client
+        .propose_outcome(&proposer, &market_id, &Side::No);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("propose"), market_id, proposer.clone()).into_val(&s.env)
+    );
+    let proposed: OutcomeProposed = data.into_val(&s.env);
+    assert_eq!(
+        proposed,
+        OutcomeProposed {
+            version: EVENT_VERSION,
+            market_id,
+            proposer,
+            outcome: Side::No,
+            bond: BOND,
+            expires_at: 3601 + 7200,
+        }
+    );
+
+    s.client.dispute(&disputer, &market_id);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("dispute"), market_id, disputer.clone()).into_val(&s.env)
+    );
+    let disputed: ProposalDisputed = data.into_val(&s.env);
+    assert_eq!(
+        disputed,
+        ProposalDisputed {
+            version: EVENT_VERSION,
+            market_id,
+            disputer,
+            bond: BOND,
+        }
+    );
+
+    s.client.invalidate_market(&s.admin, &market_id);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("invalid"), market_id).into_val(&s.env));
+    let settled: MarketSettled = data.into_val(&s.env);
+    assert_eq!(settled.status, MarketStatus::Invalid);
+}
+
+#[test]
+fn test_admin_events() {
+    let s = setup();
+    let ops = Address::generate(&s.env);
+    let market_id = create_test_market(&s, 3600);
+
+    s.client.grant_role(&Role::Pauser, &ops);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("grant"), ops.clone()).into_val(&s.env));
+    let changed: RoleChanged = data.into_val(&s.env);
+    assert_eq!(
+        changed,
+        RoleChanged {
+            version: EVENT_VERSION,
+            role: Role::Pauser,
+            account: ops.clone(),
+            granted: true,
+        }
+    );
+
+    s.client.freeze_market(&ops, &market_id);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("pause"), market_id).into_val(&s.env));
+    let paused: PauseChanged = data.into_val(&s.env);
+    assert_eq!(
+        paused,
+        PauseChanged {
+            version: EVENT_VERSION,
+            market_id: Some(market_id),
+            caller: ops.clone(),
+            paused: true,
+        }
+    );
+
+    s.client.unpause(&ops);
+    let (_, data) = last_event(&s);
+    let unpaused: PauseChanged = data.into_val(&s.env);
+    assert_eq!(unpaused.market_id, None);
+    assert!(!unpaused.paused);
+
+    s.client.set_resolver(&market_id, &ops);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("resolver"), market_id).into_val(&s.env));
+    let resolver: ResolverChanged = data.into_val(&s.env);
+    assert_eq!(resolver.resolver, ops);
+
+    s.client.propose_admin(&ops);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("adm_prop"), s.admin.clone()).into_val(&s.env));
+    let proposed: AdminTransfer = data.into_val(&s.env);
+    assert_eq!(
+        proposed,
+        AdminTransfer {
+            version: EVENT_VERSION,
+            admin: s.admin.clone(),
+            new_admin: ops.clone(),
+        }
+    );
+    s.client.accept_admin();
+    let (topics, _) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("admin"), s.admin.clone()).into_val(&s.env));
+}
+
+#[test]
+fn test_config_events() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.client.set_protocol_fee(&s.admin, &100);
+    let market_id = create_test_market(&s, 3600);
+    s.client.buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.client
+        .withdraw_protocol_fees(&s.admin, &s.token.address, &s.admin);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("withdraw"), s.admin.clone(), s.token.address.clone()).into_val(&s.env)
+    );
+    let withdrawn: FeesWithdrawn = data.into_val(&s.env);
+    assert_eq!(
+        withdrawn,
+        FeesWithdrawn {
+            version: EVENT_VERSION,
+            token: s.token.address.clone(),
+            to: s.admin.clone(),
+            amount: 100_000,
+        }
+    );
+
+    s.client.disallow_token(&s.token.address);
+    let (topics, data) = last_event(&s);
+    assert_eq!(
+        topics,
+        (symbol_short!("token"), s.token.address.clone()).into_val(&s.env)
+    );
+    let allowed: TokenAllowed = data.into_val(&s.env);
+    assert_eq!(
+        allowed,
+        TokenAllowed {
+            version: EVENT_VERSION,
+            token: s.token.address.clone(),
+            allowed: false,
+        }
+    );
+
+    s.client.set_protocol_fee(&s.admin, &250);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("fee"),).into_val(&s.env));
+    let fee: ProtocolFeeChanged = data.into_val(&s.env);
+    assert_eq!(
+        fee,
+        ProtocolFeeChanged {
+            version: EVENT_VERSION,
+            caller: s.admin.clone(),
+            fee_bps: 250,
+        }
+    );
+
+    s.client.set_dispute_params(&20_000_000, &7200);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("disp_prm"),).into_val(&s.env));
+    let params: DisputeParamsChanged = data.into_val(&s.env);
+    assert_eq!(
+        params,
+        DisputeParamsChanged {
+            version: EVENT_VERSION,
+            bond: 20_000_000,
+            liveness: 7200,
+        }
+    );
+
+    s.client.set_claim_window(&86_400);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("claim_win"),).into_val(&s.env));
+    let window: ClaimWindowChanged = data.into_val(&s.env);
+    assert_eq!(
+        window,
+        ClaimWindowChanged {
+            version: EVENT_VERSION,
+            window: 86_400,
+        }
+    );
+
+    s.client.set_permissionless_creation(&false);
+    let (topics, data) = last_event(&s);
+    assert_eq!(topics, (symbol_short!("creation"),).into_val(&s.env));
+    let creation: CreationChanged = data.into_val(&s.env);
+    assert_eq!(
+        creation,
+        CreationChanged {
+            version: EVENT_VERSION,
+            permissionless: false,
+        }
+    );
+}
+
+// ===== View Function Tests =====
+
+#[test]
+fn test_get_market_not_found() {
+    let s = setup();
+    let result = s.client.try_get_market(&999);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_get_position_no_position() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let market_id = create_test_market(&s, 3600);
+
+    let pos = s.client.get_position(&market_id, &alice);
+    assert_eq!(pos.yes_shares, 0);
+    assert_eq!(pos.no_shares, 0);
+    assert_eq!(pos.claimed, false);
+}
+
+fn listed_ids(env: &Env, markets: &soroban_sdk::Vec<MarketListing>) -> soroban_sdk::Vec<u64> {
+    let mut ids = vec![env];
+    for listing in markets.iter() {
+        ids.push_back(listing.market_id);
+    }
+    ids
+}
+
+#[test]
+fn test_list_markets_paginates() {
+    let s = setup();
+    for _ in 0..5 {
+        create_test_market(&s, 3600);
+    }
+
+    assert_eq!(listed_ids(&s.env, &s.client.list_markets(&0, &2)), vec![&s.env, 0, 1]);
+    assert_eq!(listed_ids(&s.env, &s.client.list_markets(&2, &2)), vec![&s.env, 2, 3]);
+    assert_eq!(listed_ids(&s.env, &s.client.list_markets(&4, &10)), vec![&s.env, 4]);
+    assert!(s.client.list_markets(&5, &10).is_empty());
+
+    let listing = s.client.list_markets(&1, &1).get_unchecked(0);
+    assert_eq!(listing.market_id, 1);
+    assert_eq!(listing.market.creator, s.admin);
+}
+
+#[test]
+fn test_list_markets_include_odds() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &40_000_000);
+
+    let parimutuel = create_test_market(&s, 3600);
+    let lmsr = create_lmsr_market(&s, 100_000_000);
+    let cpmm = create_cpmm_market(&s);
+    s.client.add_liquidity(&alice, &cpmm, &20_000_000);
+    s.client
+        .buy_shares(&alice, &parimutuel, &Side::Yes, &10_000_000);
+    s.client.buy_shares(&alice, &lmsr, &Side::No, &5_000_000);
+    s.client.buy_shares(&alice, &cpmm, &Side::Yes, &5_000_000);
+
+    // Each card can be drawn from the page alone
+    let listed = s.client.list_markets(&0, &10);
+    assert_eq!(listed.len(), 3);
+    for listing in listed.iter() {
+        assert_eq!(listing.odds, s.client.get_odds(&listing.market_id));
+    }
+    let open = s.client.list_markets_by_status(&StatusFilter::Open, &0, &10);
+    assert_eq!(open.get_unchecked(2).odds, s.client.get_odds(&cpmm));
+}
+
+#[test]
+fn test_list_markets_by_status() {
+    let s = setup();
+    let early = create_test_market(&s, 3600);
+    let late = create_test_market(&s, 7200);
+    let proposed = create_test_market(&s, 3600);
+    let voided = create_test_market(&s, 7200);
+
+    let by_status = |status: StatusFilter| {
+        listed_ids(&s.env, &s.client.list_markets_by_status(&status, &0, &10))
+    };
+    assert_eq!(by_status(StatusFilter::Open), vec![&s.env, early, late, proposed, voided]);
+    assert!(by_status(StatusFilter::AwaitingResolution).is_empty());
+    assert!(by_status(StatusFilter::Resolved).is_empty());
+
+    // Passing the deadline moves markets to awaiting resolution
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    assert_eq!(by_status(StatusFilter::Open), vec![&s.env, late, voided]);
+    assert_eq!(by_status(StatusFilter::AwaitingResolution), vec![&s.env, early, proposed]);
+
+    let proposer = Address::generate(&s.env);
+    s.token_admin.mint(&proposer, &100_000_000);
+    s.client.propose_outcome(&proposer, &proposed, &Side::Yes);
+    s.client.resolve_market(&s.admin, &early, &Side::No);
+    s.client.invalidate_market(&s.admin, &voided);
+
+    assert_eq!(by_status(StatusFilter::Open), vec![&s.env, late]);
+    assert_eq!(by_status(StatusFilter::AwaitingResolution), vec![&s.env, proposed]);
+    assert_eq!(by_status(StatusFilter::Resolved), vec![&s.env, early, voided]);
+}
+
+#[test]
+fn test_list_markets_by_creator() {
+    let s = setup();
+    let carol = Address::generate(&s.env);
+    create_test_market(&s, 3600);
+    let first = create_market_with_fee(&s, &carol, 0);
+    create_test_market(&s, 3600);
+    let second = create_market_with_fee(&s, &carol, 0);
+
+    let mine = s.client.list_markets_by_creator(&carol, &0, &10);
+    assert_eq!(listed_ids(&s.env, &mine), vec![&s.env, first, second]);
+    let page = s.client.list_markets_by_creator(&carol, &1, &10);
+    assert_eq!(listed_ids(&s.env, &page), vec![&s.env, second]);
+    assert_eq!(s.client.list_markets_by_creator(&s.admin, &0, &10).len(), 2);
+    assert!(s
+        .client
+        .list_markets_by_creator(&Address::generate(&s.env), &0, &10)
+        .is_empty());
+}
+
+#[test]
+fn test_get_user_positions() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &40_000_000);
+    s.token_admin.mint(&bob, &10_000_000);
+    let first = create_test_market(&s, 3600);
+    let untouched = create_test_market(&s, 3600);
+    let second = create_test_market(&s, 7200);
+
+    s.client
+        .buy_shares(&alice, &first, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&alice, &first, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &first, &Side::No, &10_000_000);
+    s.client
+        .buy_shares(&alice, &second, &Side::No, &20_000_000);
+
+    // Each market is listed once, however many times it was bought into
+    let positions = s.client.get_user_positions(&alice, &0, &10);
+    assert_eq!(positions.len(), 2);
+    let entry = positions.get_unchecked(0);
+    assert_eq!(entry.market_id, first);
+    assert_eq!(entry.market.total_yes, 20_000_000);
+    assert_eq!(entry.position.yes_shares, 20_000_000);
+    assert_eq!(entry.claimable, 0);
+    assert_eq!(positions.get_unchecked(1).market_id, second);
+    assert!(positions.iter().all(|p| p.market_id != untouched));
+
+    let page = s.client.get_user_positions(&alice, &1, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).market_id, second);
+
+    // Settled markets report what a claim would pay
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &first, &Side::Yes);
+    let entry = s.client.get_user_positions(&alice, &0, &1).get_unchecked(0);
+    assert_eq!(entry.claimable, 30_000_000);
+    let entry = s.client.get_user_positions(&bob, &0, &1).get_unchecked(0);
+    assert_eq!(entry.claimable, 0);
+
+    s.client.claim_winnings(&alice, &first);
+    let entry = s.client.get_user_positions(&alice, &0, &1).get_unchecked(0);
+    assert!(entry.position.claimed);
+    assert_eq!(entry.claimable, 0);
+}
+
+#[test]
+fn test_get_odds() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &300_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    assert_eq!(s.client.get_odds(&market_id), vec![&s.env, 5_000, 5_000]);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::No, &30_000_000);
+    assert_eq!(s.client.get_odds(&market_id), vec![&s.env, 2_500, 7_500]);
+
+    let categorical = create_categorical_market(&s);
+    assert_eq!(
+        s.client.get_odds(&categorical),
+        vec![&s.env, 3_334, 3_333, 3_333]
+    );
+    s.client
+        .buy_shares(&alice, &categorical, &Side::Outcome(1), &10_000_000);
+    assert_eq!(s.client.get_odds(&categorical), vec![&s.env, 0, 10_000, 0]);
+    s.client
+        .buy_shares(&alice, &categorical, &Side::Outcome(0), &10_000_000);
+    s.client
+        .buy_shares(&alice, &categorical, &Side::Outcome(2), &10_000_000);
+    assert_eq!(
+        s.client.get_odds(&categorical),
+        vec![&s.env, 3_334, 3_333, 3_333]
+    );
+
+    // Market makers quote their marginal price
+    let lmsr = create_lmsr_market(&s, 100_000_000);
+    assert_eq!(s.client.get_odds(&lmsr), vec![&s.env, 5_000, 5_000]);
+    s.client.buy_shares(&alice, &lmsr, &Side::Yes, &50_000_000);
+    let odds = s.client.get_odds(&lmsr);
+    assert!(odds.get_unchecked(0) > 5_000);
+    assert_eq!(odds.get_unchecked(0) + odds.get_unchecked(1), 10_000);
+
+    let cpmm = create_cpmm_market(&s);
+    s.client.add_liquidity(&alice, &cpmm, &100_000_000);
+    assert_eq!(s.client.get_odds(&cpmm), vec![&s.env, 5_000, 5_000]);
+    s.client.buy_shares(&alice, &cpmm, &Side::No, &10_000_000);
+    assert!(s.client.get_odds(&cpmm).get_unchecked(1) > 5_000);
+
+    let result = s.client.try_get_odds(&99);
+    assert_eq!(result, Err(Ok(Error::MarketNotFound)));
+}
+
+#[test]
+fn test_quote_buy_matches_payout() {
+    let s = setup();
+    let creator = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &30_000_000);
+    s.client.set_protocol_fee(&s.admin, &100);
+
+    let market_id = create_market_with_fee(&s, &creator, 200);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &30_000_000);
+
+    // 3% in fees leaves a 9.7M stake that wins the whole 38.8M pool
+    let quote = s.client.quote_buy(&market_id, &Side::Yes, &10_000_000);
+    assert_eq!(quote, 38_800_000);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.client.claim_winnings(&alice, &market_id), quote);
+
+    let result = s.client.try_quote_buy(&market_id, &Side::Yes, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::MarketClosed)));
+}
+
+#[test]
+fn test_quote_buy_market_makers() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.token_admin.mint(&s.admin, &100_000_000);
+    s.token_admin.mint(&alice, &300_000_000);
+
+    // Each LMSR or CPMM share pays 1 on a win, so the quote is the shares bought
+    let lmsr = create_lmsr_market(&s, 100_000_000);
+    let quote = s.client.quote_buy(&lmsr, &Side::Yes, &50_000_000);
+    s.client.buy_shares(&alice, &lmsr, &Side::Yes, &50_000_000);
+    assert_eq!(s.client.get_position(&lmsr, &alice).yes_shares, quote);
+
+    let cpmm = create_cpmm_market(&s);
+    let result = s.client.try_quote_buy(&cpmm, &Side::No, &10_000_000);
+    assert_eq!(result, Err(Ok(Error::NoLiquidity)));
+    s.client.add_liquidity(&alice, &cpmm, &100_000_000);
+    let quote = s.client.quote_buy(&cpmm, &Side::No, &10_000_000);
+    s.client.buy_shares(&alice, &cpmm, &Side::No, &10_000_000);
+    assert_eq!(s.client.get_position(&cpmm, &alice).no_shares, quote);
+
+    let result = s.client.try_quote_buy(&cpmm, &Side::Outcome(0), &10_000_000);
+    assert_eq!(result, Err(Ok(Error::InvalidOutcome)));
+    let result = s.client.try_quote_buy(&cpmm, &Side::Yes, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_get_claimable() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.token_admin.mint(&alice, &10_000_000);
+    s.token_admin.mint(&bob, &20_000_000);
+
+    let market_id = create_test_market(&s, 3600);
+    s.client
+        .buy_shares(&alice, &market_id, &Side::Yes, &10_000_000);
+    s.client
+        .buy_shares(&bob, &market_id, &Side::No, &20_000_000);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 0);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 3601);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 30_000_000);
+    assert_eq!(s.client.get_claimable(&market_id, &bob), 0);
+
+    let payout = s.client.claim_winnings(&alice, &market_id);
+    assert_eq!(payout, 30_000_000);
+    assert_eq!(s.client.get_claimable(&market_id, &alice), 0);
 }
 
 // ===== Full End-to-End Flow =====
@@ -439,6 +4291,12 @@ fn test_full_flow() {
         &s.admin,
         &String::from_str(&s.env, "Will ETH hit $10k?"),
         &(s.env.ledger().timestamp() + 86400), // 1 day
+        &MarketKind::Parimutuel,
+        &None,
+        &None,
+        &0,
+        &None,
+        &None,
     );
 
     // Alice buys 3 XLM YES
@@ -463,7 +4321,7 @@ fn test_full_flow() {
     s.env
         .ledger()
         .with_mut(|li| li.timestamp = li.timestamp + 86401);
-    s.client.resolve_market(&market_id, &Side::Yes);
+    s.client.resolve_market(&s.admin, &market_id, &Side::Yes);
 
     // Alice claims: only YES buyer, gets entire pool (5 XLM)
     let payout = s.client.claim_winnings(&alice, &market_id);
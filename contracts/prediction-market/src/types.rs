@@ -1,29 +1,224 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Side {
     Yes,
     No,
+    /// Index into the labels of a categorical market.
+    Outcome(u32),
 }
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum MarketStatus {
     Open,
+    /// An outcome has been proposed and is inside its dispute window.
+    Proposed,
+    /// The proposal was disputed and awaits the admin's ruling.
+    Disputed,
     Resolved(Side),
+    /// Scalar market resolved to a numeric value.
+    ResolvedScalar(i128),
+    /// Voided by the resolver; every position is refunded.
+    Invalid,
+}
+
+/// Permission granted by the admin with `grant_role`. The admin implicitly
+/// holds every role.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Role {
+    /// May create markets while permissionless creation is off.
+    MarketCreator,
+    /// May resolve or invalidate any market, alongside its own resolver.
+    Resolver,
+    /// May pause the contract and freeze markets.
+    Pauser,
+    /// May set and withdraw protocol fees.
+    FeeManager,
+}
+
+/// Secondary index over market IDs, backing the filtered listing views.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarketIndex {
+    /// Markets still open, proposed or disputed.
+    Unsettled,
+    /// Markets resolved or invalidated.
+    Settled,
+    /// Markets created by an address.
+    Creator(Address),
+    /// Markets an address holds a position in.
+    User(Address),
+}
+
+/// Status filter for `list_markets_by_status`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatusFilter {
+    /// Open and still before the trading cutoff.
+    Open,
+    /// Past the trading cutoff, or with an outcome proposed, but not yet
+    /// settled.
+    AwaitingResolution,
+    /// Resolved or invalidated.
+    Resolved,
+}
+
+/// How a market prices its shares.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarketKind {
+    /// Shares are issued 1:1 with stake and winners split the pool pro rata.
+    Parimutuel,
+    /// Logarithmic market scoring rule with liquidity parameter `b` (in
+    /// stroops). Each winning share pays out 1 stroop, backed by the creator's
+    /// subsidy of `b * ln(2)`.
+    Lmsr(i128),
+    /// Constant-product pool funded by liquidity providers. Each winning
+    /// share pays out 1 stroop.
+    Cpmm,
+    /// Parimutuel market over 3 to 16 labelled outcomes, traded and resolved
+    /// with `Side::Outcome(index)`.
+    Categorical(Vec<String>),
+    /// Parimutuel market on a numeric outcome. YES shares are LONG and NO
+    /// shares are SHORT; the pool is split linearly by where the resolved
+    /// value lands in the range.
+    Scalar(ScalarRange),
+}
+
+/// Bounds of a scalar market. Resolved values outside are clamped.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalarRange {
+    pub low: i128,
+    pub high: i128,
+}
+
+/// YES/NO reserves backing a CPMM market.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CpmmPool {
+    pub yes_reserve: i128,
+    pub no_reserve: i128,
+    /// Total LP shares outstanding.
+    pub lp_supply: i128,
+}
+
+/// How an oracle price is compared against the threshold.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Comparison {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+/// Oracle a market can be settled from once the deadline passes. Binary
+/// markets resolve YES when `price <comparison> threshold` holds; scalar
+/// markets resolve to the price itself. `threshold` uses the oracle's
+/// decimals.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OracleSpec {
+    pub oracle: Address,
+    pub asset: Symbol,
+    pub comparison: Comparison,
+    pub threshold: i128,
 }
 
 #[contracttype]
 #[derive(Clone)]
 pub struct Market {
     pub creator: Address,
+    /// Address allowed to resolve or invalidate the market. `None` defers
+    /// to the current admin, so rotating the admin key carries over.
+    pub resolver: Option<Address>,
     pub question: String,
     pub deadline: u64,
+    /// Buys, sells and liquidity deposits are refused from this timestamp
+    /// on. Never after `deadline`.
+    pub trading_closes_at: u64,
+    pub kind: MarketKind,
+    /// SEP-41 token the market is denominated in.
+    pub token: Address,
+    /// Creator fee taken from every purchase, in basis points.
+    pub creator_fee_bps: u32,
     pub status: MarketStatus,
+    /// Outstanding YES shares. For LMSR markets this is the `q_yes` term of
+    /// the cost function; for CPMM markets it excludes the pool's reserves.
     pub total_yes: i128,
+    /// Outstanding NO shares (`q_no` for LMSR markets).
     pub total_no: i128,
+    /// Outstanding shares per outcome of a categorical market; empty otherwise.
+    pub outcome_totals: Vec<i128>,
     pub pool_balance: i128,
+    /// Seconds after settlement during which claims are accepted; zero means
+    /// claims never expire. Fixed when the market is created.
+    pub claim_window: u64,
+    /// Contract clock reading (see `get_clock`) at which the market was
+    /// resolved or invalidated.
+    pub settled_at: u64,
+    /// Total paid out to holders by `claim_winnings` and `claim_many`.
+    pub claimed_total: i128,
+    /// Everything paid out of the settled pool: claims plus any sweep. Never
+    /// exceeds `pool_balance`.
+    pub total_paid_out: i128,
+    /// Shares redeemed by claims so far.
+    pub claimed_winning_shares: i128,
+}
+
+/// `Market` as stored up to schema version 2. Only `migrate` reads it.
+#[contracttype]
+#[derive(Clone)]
+pub struct MarketV2 {
+    pub creator: Address,
+    pub resolver: Address,
+    pub question: String,
+    pub deadline: u64,
+    pub kind: MarketKind,
+    pub token: Address,
+    pub creator_fee_bps: u32,
+    pub status: MarketStatus,
+    pub total_yes: i128,
+    pub total_no: i128,
+    pub outcome_totals: Vec<i128>,
+    pub pool_balance: i128,
+}
+
+/// `Market` as stored in schema version 3. Only `migrate` reads it.
+#[contracttype]
+#[derive(Clone)]
+pub struct MarketV3 {
+    pub creator: Address,
+    pub resolver: Address,
+    pub question: String,
+    pub deadline: u64,
+    pub kind: MarketKind,
+    pub token: Address,
+    pub creator_fee_bps: u32,
+    pub status: MarketStatus,
+    pub total_yes: i128,
+    pub total_no: i128,
+    pub outcome_totals: Vec<i128>,
+    pub pool_balance: i128,
+    pub claim_window: u64,
+    pub settled_at: u64,
+    pub claimed_total: i128,
+}
+
+/// A bonded outcome proposal for optimistic resolution.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proposal {
+    pub proposer: Address,
+    pub outcome: Side,
+    pub bond: i128,
+    /// End of the dispute window, on the contract clock (see `get_clock`).
+    pub expires_at: u64,
+    pub disputer: Option<Address>,
 }
 
 #[contracttype]
@@ -31,15 +226,121 @@ pub struct Market {
 pub struct Position {
     pub yes_shares: i128,
     pub no_shares: i128,
+    /// Shares per outcome of a categorical market; empty otherwise.
+    pub outcome_shares: Vec<i128>,
     pub claimed: bool,
 }
 
+/// Outcome of one market in a `claim_many` batch.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClaimStatus {
+    /// The payout was added to the batch transfer.
+    Paid,
+    AlreadyClaimed,
+    /// The market has not been resolved or invalidated yet.
+    NotSettled,
+    /// The contract is paused or the market is frozen.
+    Paused,
+    /// A losing or empty position; it is left unclaimed.
+    NoPayout,
+    /// The market's claim window has closed.
+    Expired,
+}
+
+/// Per-market entry returned by `claim_many`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimResult {
+    pub market_id: u64,
+    pub status: ClaimStatus,
+    pub payout: i128,
+}
+
+/// One token's entry in `get_solvency`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenSolvency {
+    pub token: Address,
+    /// Sum of open pools, unclaimed payouts, bonds and fees owed in `token`.
+    pub liabilities: i128,
+    /// The contract's balance of `token`.
+    pub balance: i128,
+}
+
+/// A market as returned by the listing functions.
+#[contracttype]
+#[derive(Clone)]
+pub struct MarketListing {
+    pub market_id: u64,
+    pub market: Market,
+    /// Current odds, as returned by `get_odds`.
+    pub odds: Vec<u32>,
+}
+
+/// A user's position in one market, as returned by `get_user_positions`.
+#[contracttype]
+#[derive(Clone)]
+pub struct UserPosition {
+    pub market_id: u64,
+    pub market: Market,
+    pub position: Position,
+    /// What `claim_winnings` would pay right now; zero until the market is
+    /// settled and after claiming.
+    pub claimable: i128,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
     Admin,
+    /// Storage layout version of the persisted `Market` and `Position`
+    /// entries, advanced by `migrate`.
+    SchemaVersion,
+    /// Next market id for the migration step in progress.
+    MigrationCursor,
+    /// Admin nominated by `propose_admin`, waiting to accept.
+    PendingAdmin,
+    /// Default collateral token for markets created without one.
     Token,
+    AllowedToken(Address),
     NextMarketId,
     Market(u64),
     Position(u64, Address),
+    Pool(u64),
+    LpShares(u64, Address),
+    /// Collateral an account has put into an LMSR or CPMM market, net of
+    /// fees, less what it has taken back out. Refunded if the market is
+    /// voided.
+    CostBasis(u64, Address),
+    /// Sum of a market's unrefunded `CostBasis` entries.
+    CostBasisTotal(u64),
+    Oracle(u64),
+    Proposal(u64),
+    ProposalBond,
+    Liveness,
+    /// Claim window stamped on new markets.
+    ClaimWindow,
+    /// What the contract owes in a token.
+    Liabilities(Address),
+    /// Tokens with a `Liabilities` entry.
+    LiabilityTokens,
+    ProtocolFeeBps,
+    Paused,
+    /// Ledger timestamp at which the current global pause began.
+    PausedAt,
+    /// Seconds spent globally paused before the current pause.
+    PausedTotal,
+    Frozen(u64),
+    /// Contract clock reading at which a market was frozen.
+    FrozenAt(u64),
+    Role(Role, Address),
+    /// Whether anyone may create markets, or only `MarketCreator`s.
+    PermissionlessCreation,
+    IndexLen(MarketIndex),
+    IndexEntry(MarketIndex, u32),
+    /// Position of an unsettled market in `MarketIndex::Unsettled`.
+    UnsettledSlot(u64),
+    ProtocolFees(Address),
+    CreatorFees(Address, Address),
 }
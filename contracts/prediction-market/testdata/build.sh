@@ -0,0 +1,38 @@
+#!/bin/bash
+set -e
+
+# Rebuilds the release builds the upgrade tests deploy. Each fixture is the
+# contract as of the commit that introduced its schema version, built from a
+# temporary worktree. The wasm is reproducible byte for byte with the same
+# toolchain: rustc 1.95.0 and soroban-sdk 22.0.11.
+#
+# Usage: testdata/build.sh [version...]   (default: every version)
+
+FIXTURES=(
+  "1 5ccb0e2c62b7830f2da95eb6ee311aa889085da3" # upgrade and migrate
+  "2 51eec5cdce28a152a5894f7e1c58bd2760712941" # listing indexes
+  "3 8313829fed283d5e3adef5965db002e65ba6cbc9" # claim window and sweeps
+)
+
+TESTDATA="$(cd "$(dirname "$0")" && pwd)"
+REPO="$(git -C "$TESTDATA" rev-parse --show-toplevel)"
+WORK="$(mktemp -d)"
+trap 'git -C "$REPO" worktree remove --force "$WORK/src" 2>/dev/null; rm -rf "$WORK"' EXIT
+
+for fixture in "${FIXTURES[@]}"; do
+  read -r version commit <<<"$fixture"
+  if [ $# -gt 0 ] && [[ ! " $* " =~ " $version " ]]; then
+    continue
+  fi
+
+  echo "Building v$version from ${commit:0:7}..."
+  git -C "$REPO" worktree add --quiet --detach "$WORK/src" "$commit"
+  (
+    cd "$WORK/src/contracts/prediction-market"
+    CARGO_TARGET_DIR="$WORK/target-v$version" cargo build \
+      --target wasm32-unknown-unknown --release
+  )
+  cp "$WORK/target-v$version/wasm32-unknown-unknown/release/prediction_market.wasm" \
+    "$TESTDATA/prediction_market_v$version.wasm"
+  git -C "$REPO" worktree remove --force "$WORK/src"
+done
@@ -7,6 +7,7 @@ import { CreateMarket } from './pages/CreateMarket'
 import { MarketDetail } from './pages/MarketDetail'
 import { Admin } from './pages/Admin'
 import { Faucet } from './pages/Faucet'
+import { Portfolio } from './pages/Portfolio'
 
 export default function App() {
   return (
@@ -17,6 +18,7 @@ export default function App() {
             <Route path="/" element={<Markets />} />
             <Route path="/create" element={<CreateMarket />} />
             <Route path="/market/:id" element={<MarketDetail />} />
+            <Route path="/portfolio" element={<Portfolio />} />
             <Route path="/admin" element={<Admin />} />
             <Route path="/faucet" element={<Faucet />} />
           </Route>
@@ -5,6 +5,7 @@ import { TickerBar } from './TickerBar'
 const navLinks = [
   { to: '/', label: 'Markets' },
   { to: '/create', label: 'Create' },
+  { to: '/portfolio', label: 'Portfolio' },
   { to: '/admin', label: 'Admin' },
   { to: '/faucet', label: 'Faucet' },
 ]
@@ -1,19 +1,16 @@
 import { Link } from 'react-router-dom'
 import type { Market } from '../hooks/useContract'
-import { stroopsToXlm } from '../lib/stellar'
+import { oddsToPercent, stroopsToXlm } from '../lib/stellar'
 
 interface Props {
   market: Market
   marketId: number
+  // Odds from the market listing, so cards cost no extra calls
+  odds: number[]
 }
 
-export function MarketCard({ market, marketId }: Props) {
-  const totalShares = market.total_yes + market.total_no
-  const yesPct =
-    totalShares > 0n
-      ? Number((market.total_yes * 100n) / totalShares)
-      : 50
-  const noPct = 100 - yesPct
+export function MarketCard({ market, marketId, odds }: Props) {
+  const [yesPct, noPct] = oddsToPercent(odds)
 
   const isResolved = market.status.tag === 'Resolved'
   const deadlineDate = new Date(market.deadline * 1000)
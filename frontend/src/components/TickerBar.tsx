@@ -1,6 +1,6 @@
 import { useEffect, useState } from 'react'
 import { useContract } from '../hooks/useContract'
-import { stroopsToXlm } from '../lib/stellar'
+import { oddsToPercent, stroopsToXlm } from '../lib/stellar'
 
 interface TickerItem {
   id: number
@@ -10,7 +10,7 @@ interface TickerItem {
 }
 
 export function TickerBar() {
-  const { getMarketCount, getMarket } = useContract()
+  const { getMarketCount, getMarket, getOdds } = useContract()
   const [items, setItems] = useState<TickerItem[]>([])
 
   useEffect(() => {
@@ -21,8 +21,7 @@ export function TickerBar() {
         for (let i = 0; i < Math.min(count, 10); i++) {
           try {
             const m = await getMarket(i)
-            const total = m.total_yes + m.total_no
-            const yesPct = total > 0n ? Number((m.total_yes * 100n) / total) : 50
+            const [yesPct] = oddsToPercent(await getOdds(i))
             results.push({
               id: i,
               question: m.question.length > 40 ? m.question.slice(0, 40) + '...' : m.question,
@@ -34,7 +33,7 @@ export function TickerBar() {
         setItems(results)
       } catch {}
     })()
-  }, [getMarketCount, getMarket])
+  }, [getMarketCount, getMarket, getOdds])
 
   if (items.length === 0) return null
 
@@ -16,18 +16,33 @@ export interface Market {
   creator: string
   question: string
   deadline: number
+  trading_closes_at: number
   status: { tag: string; values?: unknown[] }
   total_yes: bigint
   total_no: bigint
   pool_balance: bigint
 }
 
+export interface MarketListing {
+  id: number
+  data: Market
+  // Implied probability per side in basis points, as get_odds returns it
+  odds: number[]
+}
+
 export interface Position {
   yes_shares: bigint
   no_shares: bigint
   claimed: boolean
 }
 
+export interface UserPosition {
+  marketId: number
+  market: Market
+  position: Position
+  claimable: bigint
+}
+
 // Helper to create Soroban enum ScVal (e.g., Side::Yes -> Vec[Sym("Yes")])
 function sideScVal(side: 'Yes' | 'No'): StellarSdk.xdr.ScVal {
   return StellarSdk.xdr.ScVal.scvVec([
@@ -35,6 +50,13 @@ function sideScVal(side: 'Yes' | 'No'): StellarSdk.xdr.ScVal {
   ])
 }
 
+// MarketKind::Parimutuel (1:1 shares, pro-rata payout)
+function parimutuelKindScVal(): StellarSdk.xdr.ScVal {
+  return StellarSdk.xdr.ScVal.scvVec([
+    StellarSdk.xdr.ScVal.scvSymbol('Parimutuel'),
+  ])
+}
+
 export function useContract() {
   const { address, signTransaction } = useWallet()
 
@@ -49,18 +71,27 @@ export function useContract() {
       toU64(marketId),
     ])
     if (!result) throw new Error('Market not found')
-    const raw = fromScVal(result)
-    return {
-      creator: raw.creator,
-      question: raw.question,
-      deadline: Number(raw.deadline),
-      status: parseStatus(raw.status),
-      total_yes: BigInt(raw.total_yes),
-      total_no: BigInt(raw.total_no),
-      pool_balance: BigInt(raw.pool_balance),
-    }
+    return parseMarket(fromScVal(result))
   }, [])
 
+  // One page of markets by ID with their odds; the contract caps `limit` at 50
+  const listMarkets = useCallback(
+    async (start: number, limit: number): Promise<MarketListing[]> => {
+      const result = await queryContract(CONTRACT_ID, 'list_markets', [
+        toU64(start),
+        StellarSdk.nativeToScVal(limit, { type: 'u32' }),
+      ])
+      if (!result) return []
+      const raw = fromScVal(result) as ReturnType<typeof fromScVal>[]
+      return raw.map((entry) => ({
+        id: Number(entry.market_id),
+        data: parseMarket(entry.market),
+        odds: (entry.odds as number[]).map(Number),
+      }))
+    },
+    [],
+  )
+
   const getPosition = useCallback(
     async (marketId: number, user: string): Promise<Position> => {
       const result = await queryContract(CONTRACT_ID, 'get_position', [
@@ -78,14 +109,94 @@ export function useContract() {
     [],
   )
 
+  // Implied probability per side in basis points, priced by the contract
+  const getOdds = useCallback(async (marketId: number): Promise<number[]> => {
+    const result = await queryContract(CONTRACT_ID, 'get_odds', [
+      toU64(marketId),
+    ])
+    if (!result) return []
+    return (fromScVal(result) as number[]).map(Number)
+  }, [])
+
+  // Payout of a buy if its side wins, after fees
+  const quoteBuy = useCallback(
+    async (
+      marketId: number,
+      side: 'Yes' | 'No',
+      amount: bigint,
+    ): Promise<bigint> => {
+      const result = await queryContract(CONTRACT_ID, 'quote_buy', [
+        toU64(marketId),
+        sideScVal(side),
+        toI128(amount),
+      ])
+      if (!result) return 0n
+      return BigInt(fromScVal(result))
+    },
+    [],
+  )
+
+  // Exactly what claim_winnings would pay the user right now
+  const getClaimable = useCallback(
+    async (marketId: number, user: string): Promise<bigint> => {
+      const result = await queryContract(CONTRACT_ID, 'get_claimable', [
+        toU64(marketId),
+        toAddress(user),
+      ])
+      if (!result) return 0n
+      return BigInt(fromScVal(result))
+    },
+    [],
+  )
+
+  // A page of the user's positions with their markets and claimable payouts
+  const getUserPositions = useCallback(
+    async (
+      user: string,
+      start: number,
+      limit: number,
+    ): Promise<UserPosition[]> => {
+      const result = await queryContract(CONTRACT_ID, 'get_user_positions', [
+        toAddress(user),
+        StellarSdk.nativeToScVal(start, { type: 'u32' }),
+        StellarSdk.nativeToScVal(limit, { type: 'u32' }),
+      ])
+      if (!result) return []
+      const raw = fromScVal(result) as ReturnType<typeof fromScVal>[]
+      return raw.map((entry) => ({
+        marketId: Number(entry.market_id),
+        market: parseMarket(entry.market),
+        position: {
+          yes_shares: BigInt(entry.position.yes_shares),
+          no_shares: BigInt(entry.position.no_shares),
+          claimed: entry.position.claimed,
+        },
+        claimable: BigInt(entry.claimable),
+      }))
+    },
+    [],
+  )
+
   const createMarket = useCallback(
-    async (question: string, deadline: number) => {
+    async (question: string, deadline: number, tradingClosesAt?: number) => {
       if (!address) throw new Error('Wallet not connected')
       return invokeContract(
         address,
         CONTRACT_ID,
         'create_market',
-        [toAddress(address), toString(question), toU64(deadline)],
+        [
+          toAddress(address),
+          toString(question),
+          toU64(deadline),
+          parimutuelKindScVal(),
+          StellarSdk.xdr.ScVal.scvVoid(), // no oracle
+          StellarSdk.xdr.ScVal.scvVoid(), // admin resolves
+          StellarSdk.nativeToScVal(0, { type: 'u32' }), // no creator fee
+          StellarSdk.xdr.ScVal.scvVoid(), // default token
+          tradingClosesAt === undefined
+            ? StellarSdk.xdr.ScVal.scvVoid() // trade until the deadline
+            : toU64(tradingClosesAt),
+        ],
         signTransaction,
       )
     },
@@ -113,7 +224,7 @@ export function useContract() {
         address,
         CONTRACT_ID,
         'resolve_market',
-        [toU64(marketId), sideScVal(outcome)],
+        [toAddress(address), toU64(marketId), sideScVal(outcome)],
         signTransaction,
       )
     },
@@ -134,14 +245,50 @@ export function useContract() {
     [address, signTransaction],
   )
 
+  const claimMany = useCallback(
+    async (marketIds: number[]) => {
+      if (!address) throw new Error('Wallet not connected')
+      return invokeContract(
+        address,
+        CONTRACT_ID,
+        'claim_many',
+        [
+          toAddress(address),
+          StellarSdk.xdr.ScVal.scvVec(marketIds.map((id) => toU64(id))),
+        ],
+        signTransaction,
+      )
+    },
+    [address, signTransaction],
+  )
+
   return {
     getMarketCount,
     getMarket,
+    listMarkets,
     getPosition,
+    getUserPositions,
+    getOdds,
+    quoteBuy,
+    getClaimable,
     createMarket,
     buyShares,
     resolveMarket,
     claimWinnings,
+    claimMany,
+  }
+}
+
+function parseMarket(raw: ReturnType<typeof fromScVal>): Market {
+  return {
+    creator: raw.creator,
+    question: raw.question,
+    deadline: Number(raw.deadline),
+    trading_closes_at: Number(raw.trading_closes_at),
+    status: parseStatus(raw.status),
+    total_yes: BigInt(raw.total_yes),
+    total_no: BigInt(raw.total_no),
+    pool_balance: BigInt(raw.pool_balance),
   }
 }
 
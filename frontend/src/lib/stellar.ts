@@ -27,6 +27,13 @@ export function xlmToStroops(xlm: string): bigint {
   return whole + BigInt(fracStr)
 }
 
+// Whole-percent YES/NO split from get_odds basis points, even until loaded
+export function oddsToPercent(odds: number[]): [number, number] {
+  if (odds.length < 2) return [50, 50]
+  const yes = Math.round(odds[0] / 100)
+  return [yes, 100 - yes]
+}
+
 export async function fundWithFriendbot(address: string): Promise<void> {
   await fetch(`${FRIENDBOT_URL}?addr=${address}`)
 }
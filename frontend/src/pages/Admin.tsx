@@ -5,9 +5,11 @@ import { useContract, type Market } from '../hooks/useContract'
 import { stroopsToXlm } from '../lib/stellar'
 import toast from 'react-hot-toast'
 
+const PAGE_SIZE = 50
+
 export function Admin() {
   const { connected } = useWallet()
-  const { getMarketCount, getMarket, resolveMarket } = useContract()
+  const { listMarkets, getMarket, resolveMarket } = useContract()
 
   const [markets, setMarkets] = useState<{ id: number; data: Market }[]>([])
   const [loading, setLoading] = useState(true)
@@ -17,13 +19,11 @@ export function Admin() {
     ;(async () => {
       try {
         setLoading(true)
-        const count = await getMarketCount()
         const results: { id: number; data: Market }[] = []
-        for (let i = 0; i < count; i++) {
-          try {
-            const market = await getMarket(i)
-            results.push({ id: i, data: market })
-          } catch {}
+        for (let start = 0; ; start += PAGE_SIZE) {
+          const page = await listMarkets(start, PAGE_SIZE)
+          results.push(...page)
+          if (page.length < PAGE_SIZE) break
         }
         setMarkets(results)
       } catch {
@@ -32,7 +32,7 @@ export function Admin() {
         setLoading(false)
       }
     })()
-  }, [getMarketCount, getMarket])
+  }, [listMarkets])
 
   const handleResolve = async (marketId: number, outcome: 'Yes' | 'No') => {
     if (!connected) {
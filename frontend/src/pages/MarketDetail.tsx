@@ -2,17 +2,28 @@ import { useEffect, useState } from 'react'
 import { useParams, Link } from 'react-router-dom'
 import { useWallet } from '../hooks/useWallet'
 import { useContract, type Market, type Position } from '../hooks/useContract'
-import { stroopsToXlm, xlmToStroops } from '../lib/stellar'
+import { oddsToPercent, stroopsToXlm, xlmToStroops } from '../lib/stellar'
 import toast from 'react-hot-toast'
 
 export function MarketDetail() {
   const { id } = useParams<{ id: string }>()
   const marketId = Number(id)
   const { address, connected } = useWallet()
-  const { getMarket, getPosition, buyShares, claimWinnings } = useContract()
+  const {
+    getMarket,
+    getOdds,
+    quoteBuy,
+    getPosition,
+    getClaimable,
+    buyShares,
+    claimWinnings,
+  } = useContract()
 
   const [market, setMarket] = useState<Market | null>(null)
+  const [odds, setOdds] = useState<number[]>([])
+  const [quotes, setQuotes] = useState<{ yes: bigint; no: bigint } | null>(null)
   const [position, setPosition] = useState<Position | null>(null)
+  const [claimable, setClaimable] = useState(0n)
   const [loading, setLoading] = useState(true)
   const [amount, setAmount] = useState('')
   const [submitting, setSubmitting] = useState(false)
@@ -22,9 +33,11 @@ export function MarketDetail() {
       setLoading(true)
       const m = await getMarket(marketId)
       setMarket(m)
+      setOdds(await getOdds(marketId))
       if (address) {
         const p = await getPosition(marketId, address)
         setPosition(p)
+        setClaimable(await getClaimable(marketId, address))
       }
     } catch (err) {
       toast.error('Failed to load market')
@@ -37,6 +50,29 @@ export function MarketDetail() {
     loadData()
   }, [marketId, address])
 
+  // What the entered amount would pay out on each side, priced by the contract
+  useEffect(() => {
+    const stroops = amount ? xlmToStroops(amount) : 0n
+    if (stroops <= 0n || market?.status.tag !== 'Open') {
+      setQuotes(null)
+      return
+    }
+    let stale = false
+    Promise.all([
+      quoteBuy(marketId, 'Yes', stroops),
+      quoteBuy(marketId, 'No', stroops),
+    ])
+      .then(([yes, no]) => {
+        if (!stale) setQuotes({ yes, no })
+      })
+      .catch(() => {
+        if (!stale) setQuotes(null)
+      })
+    return () => {
+      stale = true
+    }
+  }, [amount, marketId, market, quoteBuy])
+
   const handleBuy = async (side: 'Yes' | 'No') => {
     if (!connected || !amount) return
     const stroops = xlmToStroops(amount)
@@ -94,22 +130,12 @@ export function MarketDetail() {
     )
   }
 
-  const totalShares = market.total_yes + market.total_no
-  const yesPct = totalShares > 0n ? Number((market.total_yes * 100n) / totalShares) : 50
-  const noPct = 100 - yesPct
+  const [yesPct, noPct] = oddsToPercent(odds)
   const isOpen = market.status.tag === 'Open'
   const isResolved = market.status.tag === 'Resolved'
   const deadlineDate = new Date(market.deadline * 1000)
   const isExpired = Date.now() > deadlineDate.getTime()
-  const canBuy = isOpen && !isExpired
-
-  const potentialPayout = (side: 'Yes' | 'No') => {
-    if (!position) return '0'
-    const shares = side === 'Yes' ? position.yes_shares : position.no_shares
-    const totalSide = side === 'Yes' ? market.total_yes : market.total_no
-    if (totalSide === 0n || shares === 0n) return '0'
-    return stroopsToXlm((shares * market.pool_balance) / totalSide)
-  }
+  const canBuy = isOpen && Date.now() < market.trading_closes_at * 1000
 
   return (
     <div className="max-w-3xl mx-auto">
@@ -230,6 +256,16 @@ export function MarketDetail() {
                   {submitting ? 'Processing...' : 'Buy NO'}
                 </button>
               </div>
+              {quotes && (
+                <div className="grid grid-cols-2 gap-4 mt-3 text-xs text-text-dim text-center">
+                  <span>
+                    Pays <span className="text-yes-green">{stroopsToXlm(quotes.yes)} XLM</span> if Yes
+                  </span>
+                  <span>
+                    Pays <span className="text-no-red">{stroopsToXlm(quotes.no)} XLM</span> if No
+                  </span>
+                </div>
+              )}
             </div>
           )}
         </div>
@@ -245,25 +281,20 @@ export function MarketDetail() {
               <div className="text-yes-green font-bold text-xl">
                 {stroopsToXlm(position.yes_shares)} XLM
               </div>
-              {isResolved && (
-                <div className="text-xs text-text-dim mt-2">
-                  Payout: {potentialPayout('Yes')} XLM
-                </div>
-              )}
             </div>
             <div className="bg-black/40 rounded-xl p-5 border border-no-red/10">
               <div className="text-xs text-text-dim mb-2">No Shares</div>
               <div className="text-no-red font-bold text-xl">
                 {stroopsToXlm(position.no_shares)} XLM
               </div>
-              {isResolved && (
-                <div className="text-xs text-text-dim mt-2">
-                  Payout: {potentialPayout('No')} XLM
-                </div>
-              )}
             </div>
           </div>
 
+          {isResolved && !position.claimed && (
+            <div className="text-sm text-text-dim mb-4">
+              Claimable: <span className="text-white font-bold">{stroopsToXlm(claimable)} XLM</span>
+            </div>
+          )}
           {isResolved && !position.claimed && (
             <button
               onClick={handleClaim}
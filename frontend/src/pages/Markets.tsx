@@ -1,14 +1,16 @@
 import { useEffect, useState } from 'react'
 import { Link } from 'react-router-dom'
 import { MarketCard } from '../components/MarketCard'
-import { useContract, type Market } from '../hooks/useContract'
+import { useContract, type MarketListing } from '../hooks/useContract'
 
 const categories = ['All', 'Open', 'Expired', 'Resolved'] as const
 type Category = (typeof categories)[number]
 
+const PAGE_SIZE = 50
+
 export function Markets() {
-  const { getMarketCount, getMarket } = useContract()
-  const [markets, setMarkets] = useState<{ id: number; data: Market }[]>([])
+  const { listMarkets } = useContract()
+  const [markets, setMarkets] = useState<MarketListing[]>([])
   const [loading, setLoading] = useState(true)
   const [error, setError] = useState<string | null>(null)
   const [filter, setFilter] = useState<Category>('All')
@@ -17,13 +19,11 @@ export function Markets() {
     ;(async () => {
       try {
         setLoading(true)
-        const count = await getMarketCount()
-        const results: { id: number; data: Market }[] = []
-        for (let i = 0; i < count; i++) {
-          try {
-            const market = await getMarket(i)
-            results.push({ id: i, data: market })
-          } catch {}
+        const results: MarketListing[] = []
+        for (let start = 0; ; start += PAGE_SIZE) {
+          const page = await listMarkets(start, PAGE_SIZE)
+          results.push(...page)
+          if (page.length < PAGE_SIZE) break
         }
         setMarkets(results)
       } catch (err) {
@@ -32,7 +32,7 @@ export function Markets() {
         setLoading(false)
       }
     })()
-  }, [getMarketCount, getMarket])
+  }, [listMarkets])
 
   const filteredMarkets = markets.filter(({ data }) => {
     if (filter === 'All') return true
@@ -131,8 +131,8 @@ export function Markets() {
             </div>
           ) : (
             <div className="grid grid-cols-1 md:grid-cols-2 xl:grid-cols-3 gap-6">
-              {filteredMarkets.map(({ id, data }) => (
-                <MarketCard key={id} marketId={id} market={data} />
+              {filteredMarkets.map(({ id, data, odds }) => (
+                <MarketCard key={id} marketId={id} market={data} odds={odds} />
               ))}
             </div>
           )}
@@ -0,0 +1,144 @@
+import { useCallback, useEffect, useState } from 'react'
+import { Link } from 'react-router-dom'
+import { useWallet } from '../hooks/useWallet'
+import { useContract, type UserPosition } from '../hooks/useContract'
+import { stroopsToXlm } from '../lib/stellar'
+import toast from 'react-hot-toast'
+
+const PAGE_SIZE = 50
+// The contract claims at most 8 markets per claim_many call
+const CLAIM_BATCH = 8
+
+export function Portfolio() {
+  const { address, connected } = useWallet()
+  const { getUserPositions, claimMany } = useContract()
+
+  const [positions, setPositions] = useState<UserPosition[]>([])
+  const [loading, setLoading] = useState(false)
+  const [claiming, setClaiming] = useState(false)
+
+  const loadPositions = useCallback(async () => {
+    if (!address) return
+    try {
+      setLoading(true)
+      const results: UserPosition[] = []
+      for (let start = 0; ; start += PAGE_SIZE) {
+        const page = await getUserPositions(address, start, PAGE_SIZE)
+        results.push(...page)
+        if (page.length < PAGE_SIZE) break
+      }
+      setPositions(results)
+    } catch {
+      toast.error('Failed to load positions')
+    } finally {
+      setLoading(false)
+    }
+  }, [address, getUserPositions])
+
+  useEffect(() => {
+    loadPositions()
+  }, [loadPositions])
+
+  const claimable = positions.filter((p) => p.claimable > 0n)
+  const totalClaimable = claimable.reduce((sum, p) => sum + p.claimable, 0n)
+
+  const handleClaimAll = async () => {
+    setClaiming(true)
+    try {
+      toast.loading('Claiming winnings...', { id: 'claim' })
+      for (let i = 0; i < claimable.length; i += CLAIM_BATCH) {
+        const batch = claimable.slice(i, i + CLAIM_BATCH)
+        await claimMany(batch.map((p) => p.marketId))
+      }
+      toast.success('Winnings claimed!', { id: 'claim' })
+      await loadPositions()
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : 'Claim failed', {
+        id: 'claim',
+      })
+    } finally {
+      setClaiming(false)
+    }
+  }
+
+  if (!connected) {
+    return (
+      <div className="max-w-3xl mx-auto">
+        <div className="bg-accent/5 border border-accent/20 rounded-xl p-5 text-accent text-sm text-center">
+          Connect your wallet to see your positions
+        </div>
+      </div>
+    )
+  }
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center py-24">
+        <div className="w-10 h-10 border-2 border-accent border-t-transparent rounded-full animate-spin" />
+      </div>
+    )
+  }
+
+  return (
+    <div className="max-w-3xl mx-auto">
+      {/* Breadcrumb */}
+      <div className="flex items-center gap-2 text-sm mb-8">
+        <Link to="/" className="text-text-dim hover:text-white transition-colors no-underline">Markets</Link>
+        <span className="text-text-dim">/</span>
+        <span className="text-text-secondary">Portfolio</span>
+      </div>
+
+      <div className="flex items-center justify-between gap-4 mb-8">
+        <h1 className="text-2xl font-bold">Portfolio</h1>
+        {claimable.length > 0 && (
+          <button
+            onClick={handleClaimAll}
+            disabled={claiming}
+            className="bg-accent hover:bg-accent-hover text-black px-5 py-2.5 rounded-full font-semibold text-sm transition-colors disabled:opacity-50"
+          >
+            {claiming
+              ? 'Claiming...'
+              : `Claim all (${stroopsToXlm(totalClaimable)} XLM)`}
+          </button>
+        )}
+      </div>
+
+      {positions.length === 0 ? (
+        <div className="bg-surface border border-border rounded-2xl p-8 text-center text-text-dim text-sm">
+          No positions yet
+        </div>
+      ) : (
+        <div className="space-y-4">
+          {positions.map(({ marketId, market, position, claimable }) => (
+            <Link
+              key={marketId}
+              to={`/market/${marketId}`}
+              className="block bg-surface hover:bg-surface-hover border border-border rounded-2xl p-6 transition-colors no-underline"
+            >
+              <h3 className="text-white font-semibold mb-3">{market.question}</h3>
+              <div className="flex items-center justify-between text-xs text-text-dim">
+                <span>
+                  <span className="text-yes-green">Yes {stroopsToXlm(position.yes_shares)}</span>
+                  {' '}&middot;{' '}
+                  <span className="text-no-red">No {stroopsToXlm(position.no_shares)}</span>
+                  {' '}&middot; {market.status.tag}
+                </span>
+                <span>
+                  {position.claimed ? (
+                    'Claimed'
+                  ) : claimable > 0n ? (
+                    <span className="text-accent font-semibold">
+                      {stroopsToXlm(claimable)} XLM claimable
+                    </span>
+                  ) : (
+                    'Nothing to claim'
+                  )}
+                </span>
+              </div>
+            </Link>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
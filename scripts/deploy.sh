@@ -83,4 +83,4 @@ echo "Admin: $ADMIN_ADDRESS"
 echo "XLM SAC: $XLM_SAC"
 echo ""
 echo "To create a test market:"
-echo "stellar contract invoke --id $CONTRACT_ID --source dev --network testnet -- create_market --creator $ADMIN_ADDRESS --question '\"Will BTC hit 100k?\"' --deadline $(( $(date +%s) + 86400 ))"
+echo "stellar contract invoke --id $CONTRACT_ID --source dev --network testnet -- create_market --creator $ADMIN_ADDRESS --question '\"Will BTC hit 100k?\"' --deadline $(( $(date +%s) + 86400 )) --kind '\"Parimutuel\"' --creator_fee_bps 0"